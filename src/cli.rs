@@ -0,0 +1,122 @@
+//! Command line tools, run instead of the game when arguments are given.
+
+use crate::headless::{simulate, Shot, SimulationReport};
+use crate::solver::{solve_file, SolverOptions};
+
+const USAGE: &str = "Usage:
+  coins_bevy                                     run the game
+  coins_bevy simulate <level.scn.ron> <shots.ron>  play scripted shots, shots are a RON list of (angle: degrees, strength: f32)
+  coins_bevy solve <level.scn.ron> [max moves] [beam width|all]
+                                                 search for a short solution and print its shots,
+                                                 only `all` tries every shot sequence";
+
+/// Runs the command in `args` and returns the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    match args {
+        [command, level, shots] if command == "simulate" => run_simulate(level, shots),
+        [command, level, limits @ ..] if command == "solve" && limits.len() <= 2 => {
+            let mut options = SolverOptions::default();
+            if let Some(max_moves) = limits.first() {
+                match max_moves.parse() {
+                    Ok(max_moves) => options.max_moves = max_moves,
+                    Err(_) => {
+                        eprintln!("Max moves must be a number, got {max_moves}");
+                        return 2;
+                    }
+                }
+            }
+            if let Some(beam_width) = limits.get(1) {
+                match beam_width.as_str() {
+                    "all" => options.beam_width = SolverOptions::EXHAUSTIVE,
+                    _ => match beam_width.parse() {
+                        Ok(beam_width) => options.beam_width = beam_width,
+                        Err(_) => {
+                            eprintln!("Beam width must be a number or `all`, got {beam_width}");
+                            return 2;
+                        }
+                    },
+                }
+            }
+            run_solve(level, &options)
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            2
+        }
+    }
+}
+
+fn run_simulate(level: &str, shots: &str) -> i32 {
+    let shots: Vec<Shot> = match std::fs::read_to_string(shots)
+        .map_err(|e| e.to_string())
+        .and_then(|text| ron::from_str(&text).map_err(|e| e.to_string()))
+    {
+        Ok(shots) => shots,
+        Err(e) => {
+            eprintln!("Could not read shots from {shots}: {e}");
+            return 2;
+        }
+    };
+    match simulate(level, &shots) {
+        Ok(report) => {
+            print_report(&report);
+            if report.solved {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not simulate {level}: {e}");
+            2
+        }
+    }
+}
+
+fn run_solve(level: &str, options: &SolverOptions) -> i32 {
+    match solve_file(level, options) {
+        Ok(Some(solution)) => {
+            print_report(&solution.report);
+            // Printed in the format `simulate` reads.
+            match ron::to_string(&solution.shots) {
+                Ok(shots) => println!("shots: {shots}"),
+                Err(e) => eprintln!("Could not write shots: {e}"),
+            }
+            0
+        }
+        Ok(None) if options.beam_width == SolverOptions::EXHAUSTIVE => {
+            println!(
+                "no sequence of up to {} shots from the grid solves the level",
+                options.max_moves
+            );
+            1
+        }
+        Ok(None) => {
+            // The search does not try every sequence, the level may still be solvable.
+            println!(
+                "no solution found within {} moves with beam width {}",
+                options.max_moves, options.beam_width
+            );
+            1
+        }
+        Err(e) => {
+            eprintln!("Could not solve {level}: {e}");
+            2
+        }
+    }
+}
+
+fn print_report(report: &SimulationReport) {
+    println!(
+        "{}\nmoves: {}\ncollisions: {}\nfinal position: ({:.1}, {:.1})",
+        if report.solved {
+            "solved"
+        } else {
+            "not solved"
+        },
+        report.moves,
+        report.touches,
+        report.position.x,
+        report.position.y
+    );
+}
@@ -12,3 +12,5 @@ pub const GIT_HASH: &str = env!("GIT_HASH");
 pub const GIT_DATE: &str = env!("GIT_DATE");
 
 pub const BASE_FONT: &str = "fonts/Alagard.ttf";
+
+pub const PIXELS_PER_METER: f32 = 100.0;
@@ -1,4 +1,5 @@
 use crate::consts::*;
+use crate::input::bindings::{Action, ActionInput, InputBindings};
 use bevy::app::{App, Plugin};
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext, EguiPlugin};
@@ -10,10 +11,11 @@ pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, git_info)
+        app.init_resource::<InspectorVisible>()
+            .add_systems(Startup, git_info)
             .add_systems(
                 Update,
-                inspector_ui, //.run_if(not(in_state(crate::states::MainState::Editor))),
+                (toggle_inspector, inspector_ui).chain(), //.run_if(not(in_state(crate::states::MainState::Editor))),
             )
             .add_plugins((EguiPlugin, DefaultInspectorConfigPlugin));
     }
@@ -39,26 +41,32 @@ fn git_info(mut commands: Commands, asset_server: Res<AssetServer>) {
     );
 }
 
-fn inspector_ui(
-    world: &mut World,
-    mut selected_entities: Local<SelectedEntities>,
-    mut active: Local<bool>,
-) {
-    use bevy::window::PrimaryWindow;
-    if world
-        .get_resource::<ButtonInput<KeyCode>>()
-        .unwrap()
-        .just_released(KeyCode::F1)
-    {
-        *active = !*active;
+#[derive(Resource, Default)]
+struct InspectorVisible(bool);
+
+fn toggle_inspector(actions: ActionInput, mut visible: ResMut<InspectorVisible>) {
+    if actions.just_released(Action::ToggleInspector) {
+        visible.0 = !visible.0;
     }
+}
+
+fn inspector_ui(world: &mut World, mut selected_entities: Local<SelectedEntities>) {
+    use bevy::window::PrimaryWindow;
     let mut egui_context = world
         .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
         .single(world)
         .clone();
-    if !*active {
+    if !world.resource::<InspectorVisible>().0 {
         return;
     }
+    let toggle_hint = match world
+        .resource::<InputBindings>()
+        .get(Action::ToggleInspector)
+        .first()
+    {
+        Some(binding) => format!("Press {} to toggle UI", binding.label()),
+        None => "Bind a key to toggle UI in the settings".to_string(),
+    };
     egui::SidePanel::left("hierarchy")
         .default_width(200.0)
         .show_animated(egui_context.get_mut(), true, |ui| {
@@ -68,7 +76,7 @@ fn inspector_ui(
                     .strong()
                     .color(MY_ACCENT_COLOR32),
             );
-            ui.label(egui::RichText::new("Press F1 to toggle UI").small());
+            ui.label(egui::RichText::new(toggle_hint).small());
             ui.add_space(15.0);
             egui::ScrollArea::vertical().show(ui, |ui| {
                 bevy_inspector_egui::bevy_inspector::hierarchy::hierarchy_ui(
@@ -1,7 +1,15 @@
 use crate::consts::*;
-use crate::game::components::{EndPoint, Obstacle, PlayerSpawnPoint};
+use crate::game::components::{
+    Difficulty, EndPoint, GameplaySettings, Hazard, HazardKind, LevelMetadata, Obstacle,
+    PlayerSpawnPoint, SurfaceZone, Wall,
+};
+use crate::game::materials::ObstacleMaterial;
+use crate::game::motion::ObstacleMotion;
+use crate::game::shapes::ObstacleShape;
+use crate::game::strength::StrengthCurve;
+use crate::game::COIN_RADIUS;
 use crate::input::MouseWorldPosition;
-use crate::{states::MainState, utils::exit_to_menu_on_escape};
+use crate::{states::MainState, utils::exit_to_menu_on_pause};
 use bevy::prelude::*;
 use bevy::{
     app::{Plugin, Update},
@@ -17,22 +25,46 @@ pub struct EditorMapRoot;
 #[derive(Component, Default, Copy, Clone)]
 pub struct EditorObject;
 
+/// Obstacle shown in the obstacle row of the editor, new obstacles copy it.
+#[derive(Component)]
+pub struct SelectedObstacle;
+
 #[derive(Component, Default, Debug, Copy, Clone, PartialEq)]
 pub enum ActionToDo {
     MoveObject,
     SetPlayerSpawnPoint,
     SetEndPoint,
     AddObstacleToMap,
+    SelectObstacle,
+    /// Drag from one end of the wall to the other.
+    AddWall,
+    /// Drag from one corner of the zone to the other.
+    AddZone,
+    AddHazard,
+    /// Click to add a patrol waypoint to the selected obstacle, or to move its orbit center.
+    DrawPath,
     RemoveObject,
     #[default]
     DoNothing,
 }
 
+/// Thickness of walls placed in the editor.
+const WALL_THICKNESS: f32 = 20.0;
+/// A click without a drag places a wall this long.
+const DEFAULT_WALL_LENGTH: f32 = 200.0;
+/// Drags smaller than this are clicks when adding zones.
+const MIN_ZONE_SIZE: f32 = 10.0;
+
+/// Shows where moving obstacles are as the level plays, instead of only their paths.
+#[derive(Resource, Default)]
+struct MotionPreview(bool);
+
 pub struct MapEditorPlugin;
 
 impl Plugin for MapEditorPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(OnEnter(MainState::Editor), startup)
+        app.init_resource::<MotionPreview>()
+            .add_systems(OnEnter(MainState::Editor), startup)
             .add_systems(
                 OnExit(MainState::Editor),
                 crate::utils::despawn_recursive_by_component::<EditorMapRoot>,
@@ -41,8 +73,10 @@ impl Plugin for MapEditorPlugin {
                 Update,
                 (
                     inspector_ui,
-                    exit_to_menu_on_escape,
+                    exit_to_menu_on_pause,
                     draw_objects,
+                    draw_hazards,
+                    draw_motion_paths,
                     add_missing_info,
                 )
                     .chain()
@@ -52,6 +86,7 @@ impl Plugin for MapEditorPlugin {
 }
 
 fn startup(mut commands: Commands) {
+    commands.insert_resource(LevelMetadata::default());
     commands.spawn((
         EditorMapRoot,
         Name::new("MapEditor"),
@@ -80,7 +115,15 @@ fn draw_objects(
     mut gizmos: Gizmos,
     q: Query<&GlobalTransform, With<PlayerSpawnPoint>>,
     q2: Query<(&GlobalTransform, &EndPoint), With<EndPoint>>,
-    q3: Query<(&GlobalTransform, &Obstacle), With<Obstacle>>,
+    q3: Query<(
+        &GlobalTransform,
+        &Obstacle,
+        Option<&ObstacleMaterial>,
+        Has<SelectedObstacle>,
+    )>,
+    walls: Query<(&GlobalTransform, &Wall)>,
+    zones: Query<(&GlobalTransform, &SurfaceZone)>,
+    metadata: Res<LevelMetadata>,
 ) {
     for t in q.iter() {
         let t = t.translation();
@@ -90,9 +133,92 @@ fn draw_objects(
         let t = t.translation();
         gizmos.circle_2d(Vec2::new(t.x, t.y), end_point.radius, Color::LIME_GREEN);
     }
-    for (t, obstacle) in q3.iter() {
-        let t = t.translation();
-        gizmos.circle_2d(Vec2::new(t.x, t.y), obstacle.radius, Color::RED);
+    for (t, obstacle, material, selected) in q3.iter() {
+        let (_, rotation, translation) = t.to_scale_rotation_translation();
+        let angle = rotation.to_euler(EulerRot::ZYX).0;
+        let position = translation.truncate();
+        let color = match (selected, material) {
+            (true, _) => Color::PINK,
+            (false, Some(material)) if material.tint() != Color::WHITE => material.tint(),
+            (false, _) => Color::RED,
+        };
+        obstacle
+            .shape
+            .draw(&mut gizmos, obstacle.radius, position, angle, color);
+        if let (true, ObstacleShape::Polygon(points)) = (selected, &obstacle.shape) {
+            for point in points {
+                let point = position + Vec2::from_angle(angle).rotate(*point);
+                gizmos.circle_2d(point, 4.0, color);
+            }
+        }
+    }
+    for (t, wall) in walls.iter() {
+        let (_, rotation, translation) = t.to_scale_rotation_translation();
+        let angle = rotation.to_euler(EulerRot::ZYX).0;
+        gizmos.rect_2d(translation.truncate(), angle, wall.size, Color::ORANGE);
+    }
+    for (t, zone) in zones.iter() {
+        let color = zone.tint().with_a(1.0);
+        gizmos.rect_2d(t.translation().truncate(), 0.0, zone.size, color);
+    }
+    if let Some(bounds) = metadata.bounds {
+        gizmos.rect_2d(bounds.center(), 0.0, bounds.size(), Color::ORANGE);
+    }
+}
+
+/// Hazards are drawn twice, the inner circle is the middle the coin must not cross.
+fn draw_hazards(mut gizmos: Gizmos, hazards: Query<(&GlobalTransform, &Hazard)>) {
+    for (t, hazard) in hazards.iter() {
+        let position = t.translation().truncate();
+        let color = match hazard.kind {
+            HazardKind::Pit => Color::PURPLE,
+            HazardKind::Water => Color::AQUAMARINE,
+        };
+        gizmos.circle_2d(position, hazard.radius, color);
+        gizmos.circle_2d(position, hazard.radius.min(COIN_RADIUS), color);
+    }
+}
+
+/// Patrol paths and orbits of moving obstacles, with a moving outline while previewing.
+fn draw_motion_paths(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    preview: Res<MotionPreview>,
+    obstacles: Query<(
+        &Transform,
+        &Obstacle,
+        &ObstacleMotion,
+        Has<SelectedObstacle>,
+    )>,
+) {
+    for (transform, obstacle, motion, selected) in obstacles.iter() {
+        let start = transform.translation.truncate();
+        let color = if selected { Color::PINK } else { Color::GOLD };
+        match motion {
+            ObstacleMotion::Patrol { waypoints, .. } => {
+                let path = std::iter::once(start).chain(waypoints.iter().map(|w| start + *w));
+                gizmos.linestrip_2d(path, color);
+                for waypoint in waypoints {
+                    gizmos.circle_2d(start + *waypoint, 4.0, color);
+                }
+            }
+            ObstacleMotion::Orbit { center, .. } => {
+                gizmos.circle_2d(start + *center, center.length(), color);
+                gizmos.circle_2d(start + *center, 4.0, color);
+            }
+            ObstacleMotion::Rotate { .. } => {}
+        }
+        if preview.0 {
+            let pose = motion.pose(transform, time.elapsed_seconds());
+            let angle = pose.rotation.to_euler(EulerRot::ZYX).0;
+            obstacle.shape.draw(
+                &mut gizmos,
+                obstacle.radius,
+                pose.translation.truncate(),
+                angle,
+                color,
+            );
+        }
     }
 }
 
@@ -101,6 +227,9 @@ fn inspector_ui(
     mut enum_val: Local<ActionToDo>,
     mut ui_over: Local<bool>,
     mut filename: Local<String>,
+    mut drag_start: Local<Option<Vec2>>,
+    mut zone_template: Local<SurfaceZone>,
+    mut hazard_template: Local<Hazard>,
 ) {
     use bevy::window::PrimaryWindow;
     let mut egui_context = world
@@ -128,6 +257,11 @@ fn inspector_ui(
                 for val in [
                     ActionToDo::SetPlayerSpawnPoint,
                     ActionToDo::AddObstacleToMap,
+                    ActionToDo::SelectObstacle,
+                    ActionToDo::AddWall,
+                    ActionToDo::AddZone,
+                    ActionToDo::AddHazard,
+                    ActionToDo::DrawPath,
                     ActionToDo::SetEndPoint,
                     ActionToDo::MoveObject,
                     ActionToDo::RemoveObject,
@@ -139,16 +273,82 @@ fn inspector_ui(
                 "Mouse pos: {:.2}x{:.2}: {}",
                 world_pos.x, world_pos.y, *ui_over
             ));
+            let mut metadata = world.resource::<LevelMetadata>().clone();
+            ui.horizontal(|ui| {
+                optional_value(ui, "Par moves", &mut metadata.par_moves, 3);
+                optional_value(ui, "Max collisions", &mut metadata.max_collisions, 0);
+                optional_value(ui, "Max undos", &mut metadata.max_undos, 1);
+                ui.checkbox(&mut metadata.undo_counts_as_move, "Undo counts as move");
+                egui::ComboBox::from_label("Difficulty")
+                    .selected_text(format!("{:?}", metadata.difficulty))
+                    .show_ui(ui, |ui| {
+                        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+                            ui.selectable_value(
+                                &mut metadata.difficulty,
+                                difficulty,
+                                format!("{:?}", difficulty),
+                            );
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                let defaults = GameplaySettings::default();
+                optional_force(ui, "Min force", &mut metadata.min_force, defaults.min_force);
+                optional_force(ui, "Max force", &mut metadata.max_force, defaults.max_force);
+                curve_editor(ui, &mut metadata.strength_curve);
+            });
+            ui.horizontal(|ui| {
+                bounds_editor(ui, &mut metadata.bounds);
+            });
+            let selected = world
+                .query_filtered::<Entity, With<SelectedObstacle>>()
+                .get_single(world);
+            if let Ok(selected) = selected {
+                ui.horizontal(|ui| {
+                    obstacle_editor(ui, world, selected);
+                });
+                ui.horizontal(|ui| {
+                    material_editor(ui, world, selected);
+                });
+                ui.horizontal(|ui| {
+                    motion_editor(ui, world, selected);
+                });
+            }
+            if *enum_val == ActionToDo::AddZone {
+                ui.horizontal(|ui| {
+                    zone_editor(ui, &mut zone_template);
+                });
+            }
+            if *enum_val == ActionToDo::AddHazard {
+                ui.horizontal(|ui| {
+                    hazard_editor(ui, &mut hazard_template);
+                });
+            }
+            world.resource_mut::<LevelMetadata>().set_if_neq(metadata);
             let mut text = (*filename).clone();
-            ui.text_edit_singleline(&mut text);
+            ui.horizontal(|ui| {
+                ui.label("assets/scenes/");
+                ui.text_edit_singleline(&mut text);
+            });
             *filename = text;
-            if ui.button("Save map").clicked() {
-                save_map(world, "01.scn.ron".into());
+            let has_filename = !filename.trim().is_empty();
+            if ui
+                .add_enabled(has_filename, egui::Button::new("Save map"))
+                .clicked()
+            {
+                save_map(world, filename.trim().into());
             }
-            if ui.button("Load map").clicked() {
-                load_map(world, "01.scn.ron".into());
+            if ui
+                .add_enabled(has_filename, egui::Button::new("Load map"))
+                .clicked()
+            {
+                load_map(world, filename.trim().into());
             }
             *ui_over = ui.ui_contains_pointer();
+            let mouse = world.resource::<ButtonInput<MouseButton>>();
+            if mouse.just_pressed(MouseButton::Left) && !*ui_over {
+                *drag_start = Some(world_pos);
+            }
             if world
                 .get_resource::<ButtonInput<MouseButton>>()
                 .unwrap()
@@ -179,13 +379,58 @@ fn inspector_ui(
                         }
                     }
                     ActionToDo::AddObstacleToMap => {
+                        let (obstacle, material, motion) = new_obstacle(world);
+                        let mut entity = world.spawn((transform, obstacle));
+                        if let Some(material) = material {
+                            entity.insert(material);
+                        }
+                        if let Some(motion) = motion {
+                            entity.insert(motion);
+                        }
+                        let e = entity.set_parent(world_root).id();
+                        select_obstacle(world, e);
+                    }
+                    ActionToDo::SelectObstacle => {
+                        if let Some(e) = get_closest_object_with_type::<Obstacle>(world) {
+                            select_obstacle(world, e);
+                        }
+                    }
+                    ActionToDo::AddWall => {
+                        let start = drag_start.unwrap_or(world_pos);
+                        let (transform, wall) = wall_between(start, world_pos);
+                        world
+                            .spawn((TransformBundle::from_transform(transform), wall))
+                            .set_parent(world_root);
+                    }
+                    ActionToDo::AddZone => {
+                        let start = drag_start.unwrap_or(world_pos);
+                        let size = (world_pos - start).abs();
+                        // A click without a drag uses the size of the last zone.
+                        let center = if size.min_element() >= MIN_ZONE_SIZE {
+                            zone_template.size = size;
+                            (start + world_pos) * 0.5
+                        } else {
+                            world_pos
+                        };
+                        let transform = Transform::from_translation(center.extend(0.0));
+                        world
+                            .spawn((TransformBundle::from_transform(transform), *zone_template))
+                            .set_parent(world_root);
+                    }
+                    ActionToDo::AddHazard => {
                         world
-                            .spawn((transform, Obstacle { radius: 45.0 }))
+                            .spawn((transform, *hazard_template))
                             .set_parent(world_root);
                     }
+                    ActionToDo::DrawPath => {
+                        add_path_point(world, world_pos);
+                    }
                     ActionToDo::MoveObject => {
+                        // Keeps the rotation of walls.
                         if let Some(e) = get_closest_object_with_type::<EditorObject>(world) {
-                            world.entity_mut(e).insert(transform);
+                            if let Some(mut t) = world.entity_mut(e).get_mut::<Transform>() {
+                                t.translation = world_pos.extend(0.0);
+                            }
                         }
                     }
                     ActionToDo::RemoveObject => {
@@ -195,8 +440,422 @@ fn inspector_ui(
                     }
                     ActionToDo::DoNothing => {}
                 }
+                *drag_start = None;
+            }
+        });
+}
+
+/// A copy of the selected obstacle, or a candle when nothing is selected.
+fn new_obstacle(world: &mut World) -> (Obstacle, Option<ObstacleMaterial>, Option<ObstacleMotion>) {
+    let selected = world
+        .query_filtered::<(
+            &Obstacle,
+            Option<&ObstacleMaterial>,
+            Option<&ObstacleMotion>,
+        ), With<SelectedObstacle>>()
+        .get_single(world);
+    match selected {
+        Ok((obstacle, material, motion)) => (obstacle.clone(), material.copied(), motion.cloned()),
+        Err(_) => (
+            Obstacle {
+                radius: 45.0,
+                ..default()
+            },
+            None,
+            None,
+        ),
+    }
+}
+
+fn select_obstacle(world: &mut World, entity: Entity) {
+    let previous: Vec<Entity> = world
+        .query_filtered::<Entity, With<SelectedObstacle>>()
+        .iter(world)
+        .collect();
+    for e in previous {
+        world.entity_mut(e).remove::<SelectedObstacle>();
+    }
+    world.entity_mut(entity).insert(SelectedObstacle);
+}
+
+/// Radius, rotation and shape of the selected obstacle.
+fn obstacle_editor(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let (Some(mut obstacle), Some(mut transform)) = (
+        world.get::<Obstacle>(entity).cloned(),
+        world.get::<Transform>(entity).copied(),
+    ) else {
+        return;
+    };
+    ui.label("Obstacle");
+    ui.add(
+        egui::DragValue::new(&mut obstacle.radius)
+            .clamp_range(1.0..=500.0)
+            .prefix("radius "),
+    );
+    let mut angle = transform.rotation.to_euler(EulerRot::ZYX).0.to_degrees();
+    if ui
+        .add(egui::DragValue::new(&mut angle).suffix("°"))
+        .changed()
+    {
+        transform.rotation = Quat::from_rotation_z(angle.to_radians());
+    }
+    egui::ComboBox::from_id_source("obstacle_shape")
+        .selected_text(obstacle.shape.name())
+        .show_ui(ui, |ui| {
+            for variant in ObstacleShape::variants(obstacle.radius) {
+                let selected = obstacle.shape.name() == variant.name();
+                if ui.selectable_label(selected, variant.name()).clicked() && !selected {
+                    obstacle.shape = variant;
+                }
+            }
+        });
+    match &mut obstacle.shape {
+        ObstacleShape::Circle => {}
+        ObstacleShape::Box { size } => {
+            ui.add(egui::DragValue::new(&mut size.x).prefix("w "));
+            ui.add(egui::DragValue::new(&mut size.y).prefix("h "));
+        }
+        ObstacleShape::Capsule { length } => {
+            ui.add(egui::DragValue::new(length).prefix("length "));
+        }
+        ObstacleShape::Polygon(points) => {
+            let mut removed = None;
+            for (i, point) in points.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut point.x));
+                ui.add(egui::DragValue::new(&mut point.y));
+                if ui.small_button("x").clicked() {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                points.remove(i);
+            }
+            if ui.small_button("+").clicked() {
+                // Between the last and the first point, pushed outward.
+                let new_point = match (points.first(), points.last()) {
+                    (Some(first), Some(last)) => (*first + *last) * 0.6,
+                    _ => Vec2::X * obstacle.radius,
+                };
+                points.push(new_point);
+            }
+        }
+    }
+    if let Some(mut current) = world.get_mut::<Obstacle>(entity) {
+        current.set_if_neq(obstacle);
+    }
+    if let Some(mut current) = world.get_mut::<Transform>(entity) {
+        current.set_if_neq(transform);
+    }
+}
+
+/// Motion of the selected obstacle, waypoints and the orbit center are relative to its position.
+fn motion_editor(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let current = world.get::<ObstacleMotion>(entity).cloned();
+    let mut enabled = current.is_some();
+    ui.checkbox(&mut enabled, "Motion");
+    let mut motion = current.clone().unwrap_or_default();
+    if enabled {
+        egui::ComboBox::from_id_source("obstacle_motion")
+            .selected_text(motion.name())
+            .show_ui(ui, |ui| {
+                for variant in ObstacleMotion::variants() {
+                    let selected = motion.name() == variant.name();
+                    if ui.selectable_label(selected, variant.name()).clicked() && !selected {
+                        motion = variant;
+                    }
+                }
+            });
+        match &mut motion {
+            ObstacleMotion::Patrol { waypoints, speed } => {
+                ui.add(
+                    egui::DragValue::new(speed)
+                        .clamp_range(0.0..=2000.0)
+                        .prefix("speed "),
+                );
+                let mut removed = None;
+                for (i, waypoint) in waypoints.iter_mut().enumerate() {
+                    ui.add(egui::DragValue::new(&mut waypoint.x));
+                    ui.add(egui::DragValue::new(&mut waypoint.y));
+                    if ui.small_button("x").clicked() {
+                        removed = Some(i);
+                    }
+                }
+                if let Some(i) = removed {
+                    waypoints.remove(i);
+                }
+                if ui.small_button("+").clicked() {
+                    let last = waypoints.last().copied().unwrap_or_default();
+                    waypoints.push(last + Vec2::new(100.0, 0.0));
+                }
+            }
+            ObstacleMotion::Orbit { center, speed } => {
+                ui.add(egui::DragValue::new(speed).speed(0.01).suffix(" rad/s"));
+                ui.label("center");
+                ui.add(egui::DragValue::new(&mut center.x).prefix("x "));
+                ui.add(egui::DragValue::new(&mut center.y).prefix("y "));
+            }
+            ObstacleMotion::Rotate { speed } => {
+                ui.add(egui::DragValue::new(speed).speed(0.01).suffix(" rad/s"));
+            }
+        }
+        let mut preview = world.resource::<MotionPreview>().0;
+        ui.checkbox(&mut preview, "Preview");
+        world.resource_mut::<MotionPreview>().0 = preview;
+    }
+    match (enabled, current) {
+        (true, Some(current)) if current != motion => {
+            world.entity_mut(entity).insert(motion);
+        }
+        (true, None) => {
+            world.entity_mut(entity).insert(motion);
+        }
+        (false, Some(_)) => {
+            world.entity_mut(entity).remove::<ObstacleMotion>();
+        }
+        _ => {}
+    }
+}
+
+/// Extends the patrol of the selected obstacle to `position`, or moves its orbit center there.
+/// An obstacle that does not patrol or orbit starts patrolling.
+fn add_path_point(world: &mut World, position: Vec2) {
+    let Ok((entity, transform, motion)) = world
+        .query_filtered::<(Entity, &Transform, Option<&ObstacleMotion>), With<SelectedObstacle>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let offset = position - transform.translation.truncate();
+    let motion = match motion.cloned() {
+        Some(ObstacleMotion::Patrol {
+            mut waypoints,
+            speed,
+        }) => {
+            waypoints.push(offset);
+            ObstacleMotion::Patrol { waypoints, speed }
+        }
+        Some(ObstacleMotion::Orbit { speed, .. }) => ObstacleMotion::Orbit {
+            center: offset,
+            speed,
+        },
+        _ => ObstacleMotion::Patrol {
+            waypoints: vec![offset],
+            speed: 100.0,
+        },
+    };
+    world.entity_mut(entity).insert(motion);
+}
+
+/// Physics material of the selected obstacle, obstacles without one use the defaults.
+fn material_editor(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let current = world.get::<ObstacleMaterial>(entity).copied();
+    let mut enabled = current.is_some();
+    ui.checkbox(&mut enabled, "Material");
+    let mut material = current.unwrap_or_default();
+    if enabled {
+        for (name, preset) in ObstacleMaterial::presets() {
+            if ui.selectable_label(material == preset, name).clicked() {
+                material = preset;
+            }
+        }
+        ui.add(
+            egui::DragValue::new(&mut material.restitution)
+                .speed(0.01)
+                .clamp_range(0.0..=2.0)
+                .prefix("restitution "),
+        );
+        ui.add(
+            egui::DragValue::new(&mut material.friction)
+                .speed(0.01)
+                .clamp_range(0.0..=5.0)
+                .prefix("friction "),
+        );
+        ui.add(
+            egui::DragValue::new(&mut material.bounce)
+                .clamp_range(0.0..=500.0)
+                .prefix("bounce "),
+        );
+    }
+    match (enabled, current) {
+        (true, Some(current)) if current != material => {
+            world.entity_mut(entity).insert(material);
+        }
+        (true, None) => {
+            world.entity_mut(entity).insert(material);
+        }
+        (false, Some(_)) => {
+            world.entity_mut(entity).remove::<ObstacleMaterial>();
+        }
+        _ => {}
+    }
+}
+
+/// Damping of the zones added next.
+fn zone_editor(ui: &mut egui::Ui, zone: &mut SurfaceZone) {
+    ui.label("Zone");
+    for (name, linear, angular) in SurfaceZone::PRESETS {
+        let selected = zone.linear_damping == linear && zone.angular_damping == angular;
+        if ui.selectable_label(selected, name).clicked() {
+            zone.linear_damping = linear;
+            zone.angular_damping = angular;
+        }
+    }
+    ui.add(
+        egui::DragValue::new(&mut zone.linear_damping)
+            .speed(0.1)
+            .clamp_range(0.0..=100.0)
+            .prefix("linear damping "),
+    );
+    ui.add(
+        egui::DragValue::new(&mut zone.angular_damping)
+            .speed(0.1)
+            .clamp_range(0.0..=100.0)
+            .prefix("angular damping "),
+    );
+}
+
+/// Kind and size of the hazards added next.
+fn hazard_editor(ui: &mut egui::Ui, hazard: &mut Hazard) {
+    ui.label("Hazard");
+    for kind in HazardKind::ALL {
+        ui.radio_value(&mut hazard.kind, kind, format!("{:?}", kind));
+    }
+    ui.add(
+        egui::DragValue::new(&mut hazard.radius)
+            .clamp_range(COIN_RADIUS..=500.0)
+            .prefix("radius "),
+    );
+}
+
+/// A wall from `start` to `end`, or a horizontal one when they are too close.
+fn wall_between(start: Vec2, end: Vec2) -> (Transform, Wall) {
+    let offset = end - start;
+    if offset.length() < WALL_THICKNESS {
+        let transform = Transform::from_translation(end.extend(0.0));
+        let size = Vec2::new(DEFAULT_WALL_LENGTH, WALL_THICKNESS);
+        return (transform, Wall { size });
+    }
+    let transform = Transform::from_translation(((start + end) * 0.5).extend(0.0))
+        .with_rotation(Quat::from_rotation_z(offset.y.atan2(offset.x)));
+    let size = Vec2::new(offset.length(), WALL_THICKNESS);
+    (transform, Wall { size })
+}
+
+/// Level bounds as the corners of the rectangle.
+fn bounds_editor(ui: &mut egui::Ui, value: &mut Option<Rect>) {
+    let mut enabled = value.is_some();
+    ui.checkbox(&mut enabled, "Level bounds");
+    if !enabled {
+        *value = None;
+        return;
+    }
+    let bounds = value.get_or_insert_with(|| Rect::new(-600.0, -320.0, 600.0, 320.0));
+    for (label, corner) in [("min", &mut bounds.min), ("max", &mut bounds.max)] {
+        ui.label(label);
+        ui.add(egui::DragValue::new(&mut corner.x).prefix("x "));
+        ui.add(egui::DragValue::new(&mut corner.y).prefix("y "));
+    }
+    // Keeps min below max whichever corner was dragged.
+    *bounds = Rect::from_corners(bounds.min, bounds.max);
+}
+
+fn optional_value(ui: &mut egui::Ui, label: &str, value: &mut Option<i32>, default: i32) {
+    let mut enabled = value.is_some();
+    ui.checkbox(&mut enabled, label);
+    match (enabled, value.as_mut()) {
+        (true, Some(v)) => {
+            ui.add(egui::DragValue::new(v).clamp_range(0..=99));
+        }
+        (true, None) => *value = Some(default),
+        (false, _) => *value = None,
+    }
+}
+
+/// Force as (pointer distance, impulse), like [`GameplaySettings::min_force`].
+fn optional_force(ui: &mut egui::Ui, label: &str, value: &mut Option<Vec2>, default: Vec2) {
+    let mut enabled = value.is_some();
+    ui.checkbox(&mut enabled, label);
+    match (enabled, value.as_mut()) {
+        (true, Some(v)) => {
+            ui.add(
+                egui::DragValue::new(&mut v.x)
+                    .clamp_range(0.0..=1000.0)
+                    .prefix("px "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut v.y)
+                    .clamp_range(0.0..=1000.0)
+                    .prefix("impulse "),
+            );
+        }
+        (true, None) => *value = Some(default),
+        (false, _) => *value = None,
+    }
+}
+
+fn curve_editor(ui: &mut egui::Ui, value: &mut Option<StrengthCurve>) {
+    let mut enabled = value.is_some();
+    ui.checkbox(&mut enabled, "Strength curve");
+    if !enabled {
+        *value = None;
+        return;
+    }
+    let curve = value.get_or_insert_with(StrengthCurve::default);
+    let variants = [
+        StrengthCurve::Linear,
+        StrengthCurve::Quadratic,
+        StrengthCurve::Exponential { steepness: 2.0 },
+        StrengthCurve::Keyframes(vec![Vec2::ZERO, Vec2::ONE]),
+        StrengthCurve::Asset("curves/soft.curve.ron".to_string()),
+    ];
+    egui::ComboBox::from_id_source("strength_curve")
+        .selected_text(curve.name())
+        .show_ui(ui, |ui| {
+            for variant in variants {
+                let selected = curve.name() == variant.name();
+                if ui.selectable_label(selected, variant.name()).clicked() && !selected {
+                    *curve = variant;
+                }
             }
         });
+    match curve {
+        StrengthCurve::Exponential { steepness } => {
+            ui.add(
+                egui::DragValue::new(steepness)
+                    .speed(0.1)
+                    .prefix("steepness "),
+            );
+        }
+        StrengthCurve::Keyframes(keys) => {
+            let mut removed = None;
+            for (i, key) in keys.iter_mut().enumerate() {
+                ui.add(
+                    egui::DragValue::new(&mut key.x)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut key.y)
+                        .speed(0.01)
+                        .clamp_range(0.0..=1.0),
+                );
+                if ui.small_button("x").clicked() {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                keys.remove(i);
+            }
+            if ui.small_button("+").clicked() {
+                keys.push(Vec2::ONE);
+            }
+            keys.sort_by(|a, b| a.x.total_cmp(&b.x));
+        }
+        StrengthCurve::Asset(path) => {
+            ui.text_edit_singleline(path);
+        }
+        StrengthCurve::Linear | StrengthCurve::Quadratic => {}
+    }
 }
 
 pub fn get_closest_object_with_type<T: bevy::prelude::Component>(
@@ -227,6 +886,7 @@ pub fn save_map(world: &mut World, filename: String) {
     let mut scene_world = World::new();
     let type_registry = world.resource::<AppTypeRegistry>().clone();
     scene_world.insert_resource(type_registry);
+    scene_world.insert_resource(world.resource::<LevelMetadata>().clone());
 
     for (e, t) in world
         .query_filtered::<(Entity, &Transform), With<EditorObject>>()
@@ -243,6 +903,21 @@ pub fn save_map(world: &mut World, filename: String) {
         if let Some(obj) = world.entity(e).get::<Obstacle>() {
             entity_mut.insert(obj.clone());
         }
+        if let Some(obj) = world.entity(e).get::<Wall>() {
+            entity_mut.insert(obj.clone());
+        }
+        if let Some(obj) = world.entity(e).get::<ObstacleMaterial>() {
+            entity_mut.insert(*obj);
+        }
+        if let Some(obj) = world.entity(e).get::<SurfaceZone>() {
+            entity_mut.insert(*obj);
+        }
+        if let Some(obj) = world.entity(e).get::<Hazard>() {
+            entity_mut.insert(*obj);
+        }
+        if let Some(obj) = world.entity(e).get::<ObstacleMotion>() {
+            entity_mut.insert(obj.clone());
+        }
     }
 
     let scene = DynamicScene::from_world(&scene_world);
@@ -273,6 +948,8 @@ fn load_map(world: &mut World, filename: String) {
         .get_entity_mut(world_root)
         .unwrap()
         .despawn_recursive();
+    // Replaced by the metadata from the scene file, if it has any.
+    world.insert_resource(LevelMetadata::default());
 
     let scene = world
         .get_resource_mut::<AssetServer>()
@@ -1,5 +1,11 @@
 use bevy::prelude::*;
 use bevy::prelude::{Component, Resource};
+use bevy_rapier2d::prelude::Velocity;
+use serde::{Deserialize, Serialize};
+
+use crate::game::shapes::ObstacleShape;
+use crate::game::strength::StrengthCurve;
+use crate::game::COIN_LINEAR_DAMPING;
 
 #[derive(Debug, Reflect, Component, Default, Clone)]
 #[reflect(Component)]
@@ -15,21 +21,87 @@ pub struct PlayerControlled;
 #[derive(Component)]
 pub struct PointerArrow;
 
+/// Plays on the arrow once an aim ends: it flies off when the coin is shot
+/// and shrinks in place when the aim is cancelled.
+#[derive(Component)]
+pub struct ArrowRelease {
+    pub shoot: bool,
+    pub timer: Timer,
+    pub start_scale: Vec3,
+}
+
+#[derive(Component)]
+pub enum HudButton {
+    Restart,
+    Undo,
+}
+
+/// Which way the coin goes relative to the pointer or stick.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimMode {
+    /// The coin is shot toward the pointer.
+    #[default]
+    TowardPointer,
+    /// Slingshot style, dragging away from the target launches the coin the opposite way.
+    PullBack,
+}
+
+impl AimMode {
+    pub const ALL: [AimMode; 2] = [AimMode::TowardPointer, AimMode::PullBack];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AimMode::TowardPointer => "Toward pointer",
+            AimMode::PullBack => "Pull back (slingshot)",
+        }
+    }
+
+    /// Shot direction for a pointer or stick `offset` from the coin.
+    pub fn shot_direction(&self, offset: Vec2) -> Vec2 {
+        match self {
+            AimMode::TowardPointer => offset.normalize(),
+            AimMode::PullBack => -offset.normalize(),
+        }
+    }
+}
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct GameplaySettings {
+    /// Pointer distance below which there is no shot, and the weakest impulse.
     pub min_force: Vec2,
+    /// Pointer distance giving the strongest shot, and its impulse.
     pub max_force: Vec2,
+    pub curve: StrengthCurve,
+    /// Keyframes loaded for a [`StrengthCurve::Asset`] curve, see [`GameplaySettings::active_curve`].
+    #[reflect(ignore)]
+    pub loaded_curve: Option<StrengthCurve>,
+    pub aim_mode: AimMode,
 }
 
-#[derive(Resource, Reflect, Default)]
+#[derive(Resource, Reflect, Default, Clone, Debug)]
 #[reflect(Resource)]
 pub struct GameplayProgress {
     pub touches: i32,
     pub moves: i32,
+    pub undos: i32,
     pub is_inside_end_place: bool,
 }
 
+/// State of the player coin right before a shot, used to take the shot back.
+#[derive(Reflect, Clone, Debug)]
+pub struct ShotSnapshot {
+    pub transform: Transform,
+    pub velocity: Velocity,
+    pub progress: GameplayProgress,
+}
+
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct ShotHistory {
+    pub shots: Vec<ShotSnapshot>,
+}
+
 #[derive(Debug, Reflect, Component, Default, Clone)]
 #[reflect(Component)]
 pub struct PlayerSpawnPoint;
@@ -40,31 +112,221 @@ pub struct EndPoint {
     pub radius: f32,
 }
 
-#[derive(Debug, Reflect, Component, Default, Clone)]
+#[derive(Debug, Reflect, Component, Default, Clone, PartialEq)]
 #[reflect(Component)]
 pub struct Obstacle {
     pub radius: f32,
+    #[reflect(default)]
+    pub shape: ObstacleShape,
+}
+
+/// Solid rectangle centered on the transform, which also sets its rotation.
+/// A thin wall works as a segment.
+#[derive(Debug, Reflect, Component, Default, Clone)]
+#[reflect(Component)]
+pub struct Wall {
+    pub size: Vec2,
+}
+
+/// Rectangle centered on the transform that changes the damping of the coin
+/// while they overlap, to make it slide further or stop sooner.
+#[derive(Debug, Reflect, Component, Clone, Copy, PartialEq)]
+#[reflect(Component, Default)]
+pub struct SurfaceZone {
+    pub size: Vec2,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+}
+
+impl Default for SurfaceZone {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(200.0, 150.0),
+            linear_damping: 18.0,
+            angular_damping: 20.0,
+        }
+    }
+}
+
+impl SurfaceZone {
+    /// Name, linear and angular damping of the usual surfaces.
+    pub const PRESETS: [(&'static str, f32, f32); 3] = [
+        ("Sand", 18.0, 20.0),
+        ("Carpet", 10.0, 14.0),
+        ("Ice", 1.5, 2.0),
+    ];
+
+    /// Sandy when the zone slows the coin down, icy when it speeds it up.
+    pub fn tint(&self) -> Color {
+        if self.linear_damping > COIN_LINEAR_DAMPING {
+            Color::rgba(0.85, 0.7, 0.4, 0.5)
+        } else {
+            Color::rgba(0.7, 0.9, 1.0, 0.5)
+        }
+    }
+}
+
+/// Sensors other than the end place the coin is in, in the order it entered them.
+/// The last [`SurfaceZone`] sets its damping.
+#[derive(Component, Default, Clone)]
+pub struct SensorContacts(pub Vec<Entity>);
+
+/// What a [`Hazard`] looks like, both reset the coin the same way.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HazardKind {
+    #[default]
+    Pit,
+    Water,
+}
+
+impl HazardKind {
+    pub const ALL: [HazardKind; 2] = [HazardKind::Pit, HazardKind::Water];
+
+    pub fn color(&self) -> Color {
+        match self {
+            HazardKind::Pit => Color::rgb(0.12, 0.08, 0.1),
+            HazardKind::Water => Color::rgb(0.15, 0.35, 0.75),
+        }
+    }
+}
+
+/// Circle the coin must not stop in or cross the middle of. When it does it goes
+/// back to where it rested before the shot and the player gets a penalty move.
+#[derive(Debug, Reflect, Component, Clone, Copy, PartialEq)]
+#[reflect(Component, Default)]
+pub struct Hazard {
+    pub radius: f32,
+    #[reflect(default)]
+    pub kind: HazardKind,
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Self {
+            radius: 50.0,
+            kind: HazardKind::default(),
+        }
+    }
+}
+
+/// Where the coin started the level, for hazards hit before the first shot.
+#[derive(Component, Clone, Copy)]
+pub struct SpawnPosition(pub Vec2);
+
+/// Walls spawned around [`LevelMetadata::bounds`], not stored in the scene.
+#[derive(Component)]
+pub struct LevelBoundary;
+
+/// How much help the player gets while aiming.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Trajectory preview shows the first two bounces.
+    Easy,
+    /// Trajectory preview ends at the first bounce.
+    #[default]
+    Normal,
+    /// No trajectory preview.
+    Hard,
+}
+
+impl Difficulty {
+    pub fn preview_bounces(&self) -> Option<usize> {
+        match self {
+            Difficulty::Easy => Some(2),
+            Difficulty::Normal => Some(1),
+            Difficulty::Hard => None,
+        }
+    }
+}
+
+/// Level wide data, stored as a resource in the scene file.
+#[derive(Resource, Reflect, Default, Clone, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct LevelMetadata {
+    /// Moves needed to earn the par star.
+    pub par_moves: Option<i32>,
+    /// Collisions allowed to earn the bonus star.
+    pub max_collisions: Option<i32>,
+    /// How many shots can be taken back, unlimited when not set.
+    #[reflect(default)]
+    pub max_undos: Option<i32>,
+    /// Shots taken back still count as moves.
+    #[reflect(default)]
+    pub undo_counts_as_move: bool,
+    #[reflect(default)]
+    pub difficulty: Difficulty,
+    /// Replaces [`GameplaySettings::min_force`] on this level.
+    #[reflect(default)]
+    pub min_force: Option<Vec2>,
+    /// Replaces [`GameplaySettings::max_force`] on this level.
+    #[reflect(default)]
+    pub max_force: Option<Vec2>,
+    #[reflect(default)]
+    pub strength_curve: Option<StrengthCurve>,
+    /// Area the coin cannot leave, walls are put around it.
+    #[reflect(default)]
+    pub bounds: Option<Rect>,
+}
+
+impl LevelMetadata {
+    pub const MAX_STARS: u8 = 3;
+
+    /// One star for finishing the level and one for each target met.
+    /// Targets that are not set are always met.
+    pub fn stars(&self, progress: &GameplayProgress) -> u8 {
+        let par = self.par_moves.is_none_or(|par| progress.moves <= par);
+        let collisions = self
+            .max_collisions
+            .is_none_or(|max| progress.touches <= max);
+        1 + par as u8 + collisions as u8
+    }
+
+    pub fn can_undo(&self, progress: &GameplayProgress) -> bool {
+        self.max_undos.is_none_or(|max| progress.undos < max)
+    }
 }
 
 impl GameplayProgress {
     pub fn reset(&mut self) {
         self.touches = 0;
         self.moves = 0;
+        self.undos = 0;
         self.is_inside_end_place = false;
     }
 }
 
 impl GameplaySettings {
+    /// The curve shots follow, the loaded keyframes of an asset curve.
+    pub fn active_curve(&self) -> &StrengthCurve {
+        match (&self.curve, &self.loaded_curve) {
+            (StrengthCurve::Asset(_), Some(loaded)) => loaded,
+            _ => &self.curve,
+        }
+    }
+
     pub fn get_shoot_strength(&self, distance: f32) -> Option<f32> {
         if distance < self.min_force.x {
             return None;
         }
-        let strength = distance.min(self.max_force.x) / self.max_force.x
-            * (self.max_force.y - self.min_force.y)
+        let pull = distance.min(self.max_force.x) / self.max_force.x;
+        let strength = self.active_curve().sample(pull) * (self.max_force.y - self.min_force.y)
             + self.min_force.y;
-        eprintln!("Distance {distance} with strength: {strength}");
         Some(strength)
     }
+
+    /// Pointer distance from the coin that gives `strength`.
+    pub fn pull_distance(&self, strength: f32) -> f32 {
+        let fraction = (strength - self.min_force.y) / (self.max_force.y - self.min_force.y);
+        self.active_curve().inverse(fraction) * self.max_force.x
+    }
+
+    /// Strength for analog inputs, `fraction` 0 is the weakest shot and 1 the strongest.
+    pub fn strength_at(&self, fraction: f32) -> f32 {
+        let distance =
+            self.min_force.x + (self.max_force.x - self.min_force.x) * fraction.clamp(0.0, 1.0);
+        self.get_shoot_strength(distance)
+            .unwrap_or(self.min_force.y)
+    }
 }
 
 impl Default for GameplaySettings {
@@ -72,6 +334,62 @@ impl Default for GameplaySettings {
         GameplaySettings {
             min_force: Vec2::new(25.0, 1.0),
             max_force: Vec2::new(150.0, 200.0),
+            curve: StrengthCurve::default(),
+            loaded_curve: None,
+            aim_mode: AimMode::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(moves: i32, touches: i32) -> GameplayProgress {
+        GameplayProgress {
+            moves,
+            touches,
+            ..default()
         }
     }
+
+    #[test]
+    fn finishing_without_targets_earns_all_stars() {
+        let metadata = LevelMetadata::default();
+        assert_eq!(metadata.stars(&progress(10, 10)), LevelMetadata::MAX_STARS);
+    }
+
+    #[test]
+    fn each_target_met_earns_a_star() {
+        let metadata = LevelMetadata {
+            par_moves: Some(2),
+            max_collisions: Some(1),
+            ..default()
+        };
+        assert_eq!(metadata.stars(&progress(2, 1)), 3);
+        assert_eq!(metadata.stars(&progress(3, 1)), 2);
+        assert_eq!(metadata.stars(&progress(2, 2)), 2);
+        assert_eq!(metadata.stars(&progress(3, 2)), 1);
+    }
+
+    #[test]
+    fn undos_are_limited_by_the_level() {
+        let used = |undos| GameplayProgress { undos, ..default() };
+
+        let unlimited = LevelMetadata::default();
+        assert!(unlimited.can_undo(&used(100)));
+
+        let limited = LevelMetadata {
+            max_undos: Some(1),
+            ..default()
+        };
+        assert!(limited.can_undo(&used(0)));
+        assert!(!limited.can_undo(&used(1)));
+
+        let none = LevelMetadata {
+            max_undos: Some(0),
+            ..default()
+        };
+        assert!(!none.can_undo(&used(0)));
+    }
 }
@@ -0,0 +1,74 @@
+use crate::game::components::*;
+use crate::game::spin::{physics_dt, Spin};
+use crate::game::{GameProgressEvent, COIN_RADIUS, REST_SPEED};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+type CoinQuery<'a> = (
+    &'a mut Transform,
+    &'a mut Velocity,
+    &'a mut ExternalImpulse,
+    &'a mut ExternalForce,
+    &'a mut Spin,
+    &'a SensorContacts,
+    &'a SpawnPosition,
+);
+
+/// Puts the coin back where it rested before the last shot when it stops in a hazard
+/// or crosses its middle, adding a penalty move.
+pub fn check_hazards(
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    hazards: Query<(&Hazard, &GlobalTransform)>,
+    mut coin: Query<CoinQuery, With<PlayerControlled>>,
+    history: Res<ShotHistory>,
+    mut progress: ResMut<GameplayProgress>,
+    mut ui_event: EventWriter<GameProgressEvent>,
+) {
+    let Ok((mut transform, mut velocity, mut impulse, mut force, mut spin, contacts, spawn)) =
+        coin.get_single_mut()
+    else {
+        return;
+    };
+    let position = transform.translation.truncate();
+    // Where the coin was at the start of the last physics step.
+    let previous = position - velocity.linvel * physics_dt(&config, &time);
+    let resting = velocity.linvel.length() < REST_SPEED && impulse.impulse == Vec2::ZERO;
+    let fell = contacts.0.iter().any(|e| {
+        let Ok((hazard, hazard_transform)) = hazards.get(*e) else {
+            return false;
+        };
+        let center = hazard_transform.translation().truncate();
+        let crossed = closest_point(previous, position, center).distance(center)
+            <= COIN_RADIUS.min(hazard.radius);
+        crossed || (resting && position.distance(center) <= hazard.radius)
+    });
+    if !fell {
+        return;
+    }
+    let target = history
+        .shots
+        .last()
+        .map_or(spawn.0, |shot| shot.transform.translation.truncate());
+    // A level that starts inside a hazard would otherwise add a move every frame.
+    if position.distance(target) < 1.0 {
+        return;
+    }
+    transform.translation = target.extend(transform.translation.z);
+    *velocity = Velocity::zero();
+    *impulse = ExternalImpulse::default();
+    *force = ExternalForce::default();
+    *spin = Spin::default();
+    progress.moves += 1;
+    ui_event.send(GameProgressEvent);
+}
+
+fn closest_point(start: Vec2, end: Vec2, point: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
@@ -0,0 +1,99 @@
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use thiserror::Error;
+
+pub const LEVEL_MANIFEST: &str = "campaign.levels.ron";
+
+/// Ordered list of the levels making up the campaign.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct LevelManifest {
+    pub levels: Vec<LevelInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelInfo {
+    pub id: String,
+    pub name: String,
+    /// Path of the `.scn.ron` file, relative to the assets folder.
+    pub scene: String,
+}
+
+#[derive(Resource)]
+pub struct LevelManifestHandle(pub Handle<LevelManifest>);
+
+/// Index in the [`LevelManifest`] of the level that is played.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct CurrentLevel {
+    pub index: usize,
+}
+
+/// Read access to the loaded [`LevelManifest`].
+#[derive(SystemParam)]
+pub struct Campaign<'w> {
+    handle: Res<'w, LevelManifestHandle>,
+    manifests: Res<'w, Assets<LevelManifest>>,
+}
+
+impl<'w> Campaign<'w> {
+    /// The manifest is loaded asynchronously, levels can't be started before that.
+    pub fn is_loaded(&self) -> bool {
+        self.manifests.contains(&self.handle.0)
+    }
+
+    pub fn levels(&self) -> &[LevelInfo] {
+        self.manifests
+            .get(&self.handle.0)
+            .map(|manifest| manifest.levels.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&LevelInfo> {
+        self.levels().get(index)
+    }
+
+    pub fn has_next(&self, index: usize) -> bool {
+        index + 1 < self.levels().len()
+    }
+}
+
+#[derive(Default)]
+pub struct LevelManifestLoader;
+
+#[derive(Debug, Error)]
+pub enum LevelManifestLoaderError {
+    #[error("Could not read level manifest: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level manifest: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for LevelManifestLoader {
+    type Asset = LevelManifest;
+    type Settings = ();
+    type Error = LevelManifestLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<LevelManifest>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["levels.ron"]
+    }
+}
+
+pub fn load_level_manifest(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LevelManifestHandle(asset_server.load(LEVEL_MANIFEST)));
+}
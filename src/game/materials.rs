@@ -0,0 +1,114 @@
+use crate::game::components::PlayerControlled;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// How an obstacle responds to the coin. Restitution and friction are multiplied
+/// with the values of the coin, so the defaults behave like an obstacle without a material.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct ObstacleMaterial {
+    pub restitution: f32,
+    pub friction: f32,
+    /// Impulse added to the coin when it bounces off, like a pinball bumper.
+    pub bounce: f32,
+}
+
+impl Default for ObstacleMaterial {
+    fn default() -> Self {
+        Self {
+            restitution: 0.5,
+            friction: 1.0,
+            bounce: 0.0,
+        }
+    }
+}
+
+const BUMPER_TINT: Color = Color::rgb(1.0, 0.45, 0.45);
+const STICKY_TINT: Color = Color::rgb(0.6, 0.8, 0.35);
+const ICE_TINT: Color = Color::rgb(0.6, 0.85, 1.0);
+
+impl ObstacleMaterial {
+    pub const BUMPER: Self = Self {
+        restitution: 1.0,
+        friction: 1.0,
+        bounce: 40.0,
+    };
+    /// Kills the bounce, the coin stops next to it.
+    pub const STICKY: Self = Self {
+        restitution: 0.0,
+        friction: 2.0,
+        bounce: 0.0,
+    };
+    /// Does not grip the coin, it keeps its spin and slides along.
+    pub const ICE: Self = Self {
+        restitution: 0.5,
+        friction: 0.0,
+        bounce: 0.0,
+    };
+
+    pub fn presets() -> [(&'static str, Self); 4] {
+        [
+            ("Default", Self::default()),
+            ("Bumper", Self::BUMPER),
+            ("Sticky", Self::STICKY),
+            ("Ice", Self::ICE),
+        ]
+    }
+
+    /// Sprite color showing what the obstacle does, white when it is ordinary.
+    pub fn tint(&self) -> Color {
+        if self.bounce > 0.0 {
+            BUMPER_TINT
+        } else if self.restitution < 0.2 {
+            STICKY_TINT
+        } else if self.friction < 0.1 {
+            ICE_TINT
+        } else {
+            Color::WHITE
+        }
+    }
+
+    pub fn restitution(&self) -> Restitution {
+        Restitution {
+            coefficient: self.restitution,
+            combine_rule: CoefficientCombineRule::Multiply,
+        }
+    }
+
+    pub fn friction(&self) -> Friction {
+        Friction {
+            coefficient: self.friction,
+            combine_rule: CoefficientCombineRule::Multiply,
+        }
+    }
+}
+
+/// Pushes the coin away from bumpers it just hit.
+pub fn bounce_off_materials(
+    mut collision_events: EventReader<CollisionEvent>,
+    materials: Query<(&ObstacleMaterial, &GlobalTransform)>,
+    mut coins: Query<(&mut ExternalImpulse, &Velocity, &GlobalTransform), With<PlayerControlled>>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(e1, e2, _) = event else {
+            continue;
+        };
+        for (coin, other) in [(*e1, *e2), (*e2, *e1)] {
+            let (Ok((mut impulse, velocity, coin_transform)), Ok((material, transform))) =
+                (coins.get_mut(coin), materials.get(other))
+            else {
+                continue;
+            };
+            if material.bounce <= 0.0 {
+                continue;
+            }
+            // The collision is already solved, so the coin moves away from the obstacle.
+            let away = velocity.linvel.try_normalize().unwrap_or_else(|| {
+                (coin_transform.translation() - transform.translation())
+                    .truncate()
+                    .normalize_or_zero()
+            });
+            impulse.impulse += away * material.bounce;
+        }
+    }
+}
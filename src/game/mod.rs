@@ -1,39 +1,121 @@
 pub mod components;
+pub mod hazards;
+pub mod levels;
+pub mod materials;
+pub mod motion;
+pub mod records;
+pub mod shapes;
+pub mod sound;
+pub mod spin;
+pub mod strength;
+pub mod trajectory;
 
 use crate::consts;
 use crate::game::components::*;
+use crate::game::hazards::check_hazards;
+use crate::game::levels::*;
+use crate::game::materials::*;
+use crate::game::motion::*;
+use crate::game::records::*;
+use crate::game::shapes::ObstacleShape;
+use crate::game::sound::*;
+use crate::game::spin::*;
+use crate::game::strength::*;
+use crate::input::bindings::{Action, ActionInput};
 use crate::input::{AimingEndedEvent, AimingEvent, MainCamera};
-use crate::states::MainState;
+use crate::states::settings::{Music, UserSettings};
+use crate::states::{button_text_style, spawn_button, GameState, MainState, MenuButtonActivated};
 use crate::utils::*;
+use bevy::audio::AddAudioSource;
+use bevy::ecs::schedule::SystemConfigs;
 use bevy::prelude::*;
-use bevy::sprite::Anchor;
+use bevy::sprite::{Anchor, MaterialMesh2dBundle};
 use bevy_rapier2d::prelude::Sensor;
 use bevy_rapier2d::prelude::*;
 #[derive(Event)]
 pub struct GameProgressEvent;
 
+/// Sent once when the coin comes to rest inside the end place.
+#[derive(Event, Clone, Debug)]
+pub struct LevelCompleted {
+    pub progress: GameplayProgress,
+    pub stars: u8,
+}
+
+/// Reloads the current level in place, without leaving [`MainState::Game`].
+/// Sent by Retry on the results overlay and the pause menu, and by the restart key and HUD button.
+#[derive(Event)]
+pub struct RestartLevel;
+
+/// Puts the coin back where it was before the last shot.
+#[derive(Event)]
+pub struct UndoShot;
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<GameplaySettings>()
+        register_level_types(app);
+        app.register_type::<AimMode>()
+            .register_type::<GameplaySettings>()
+            .register_type::<Spin>()
             .register_type::<GameplayProgress>()
+            .add_audio_source::<ShotSound>()
             .init_resource::<GameplaySettings>()
             .init_resource::<GameplayProgress>()
-            .register_type::<PlayerSpawnPoint>()
-            .register_type::<EndPoint>()
-            .register_type::<Obstacle>()
-            .register_type::<GameRootObject>()
+            .register_type::<ShotHistory>()
+            .init_resource::<ShotHistory>()
+            .register_type::<LevelClock>()
+            .init_resource::<LevelClock>()
+            .init_resource::<LevelMetadata>()
+            .register_type::<CurrentLevel>()
+            .init_resource::<CurrentLevel>()
+            .register_type::<LevelRecord>()
+            .register_type::<LevelRecords>()
+            .insert_resource(crate::save::load::<LevelRecords>(RECORDS_SAVE_KEY))
+            .init_asset::<LevelManifest>()
+            .init_asset_loader::<LevelManifestLoader>()
+            .init_asset::<StrengthCurveAsset>()
+            .init_asset_loader::<StrengthCurveLoader>()
             .add_event::<GameProgressEvent>()
+            .add_event::<LevelCompleted>()
+            .add_event::<RestartLevel>()
+            .add_event::<UndoShot>()
             .add_systems(
                 OnEnter(MainState::Game),
-                (load_scene_system, setup_world, reset_progress),
+                (load_scene_system, setup_world, reset_progress, start_music),
+            )
+            .add_systems(
+                Update,
+                (
+                    despawn_recursive_by_component::<GameRootObject>,
+                    load_scene_system,
+                    setup_world,
+                    reset_progress,
+                )
+                    .chain()
+                    .run_if(in_state(MainState::Game).and_then(on_event::<RestartLevel>())),
             )
             .add_systems(
                 OnExit(MainState::Game),
-                despawn_recursive_by_component::<GameRootObject>,
+                (
+                    despawn_recursive_by_component::<GameRootObject>,
+                    despawn_recursive_by_component::<Music>,
+                ),
+            )
+            .add_systems(Startup, (setup_graphics, load_level_manifest))
+            .add_systems(
+                Update,
+                (
+                    save_records,
+                    (
+                        apply_level_overrides,
+                        sort_curve_keyframes,
+                        resolve_curve_asset,
+                    )
+                        .chain(),
+                ),
             )
-            .add_systems(Startup, setup_graphics)
             .add_systems(
                 PostUpdate,
                 (display_events).run_if(in_state(MainState::Game)),
@@ -41,45 +123,338 @@ impl Plugin for GamePlugin {
             .add_systems(
                 Update,
                 (
-                    add_elements,
-                    arrow_display,
-                    velocity_changed,
+                    level_physics_systems(),
+                    add_sprites,
+                    add_obstacle_sprites,
+                    add_hazard_sprites,
+                    // Spin would keep decaying while the physics is frozen.
+                    coin_physics_systems().run_if(not(in_state(GameState::Paused))),
+                    (arrow_display, animate_arrow_release).chain(),
+                    play_shot_sound,
+                    draw_slingshot_band.run_if(in_state(GameState::Playing)),
+                    draw_spin_indicator.run_if(in_state(GameState::Playing)),
+                    velocity_changed.run_if(in_state(GameState::Playing)),
+                    store_level_record,
                     update_ui,
-                    exit_to_menu_on_escape,
+                    restart_on_key,
+                    undo_on_key,
+                    hud_buttons,
+                    undo_shot.run_if(in_state(GameState::Playing)),
+                    trajectory::draw_trajectory.run_if(in_state(GameState::Playing)),
                 )
                     .run_if(in_state(MainState::Game)),
             );
     }
 }
 
-fn add_elements(
+/// Registers everything a level scene file may contain.
+pub fn register_level_types(app: &mut App) {
+    app.register_type::<PlayerSpawnPoint>()
+        .register_type::<EndPoint>()
+        .register_type::<Obstacle>()
+        .register_type::<ObstacleShape>()
+        .register_type::<ObstacleMaterial>()
+        .register_type::<Wall>()
+        .register_type::<SurfaceZone>()
+        .register_type::<Hazard>()
+        .register_type::<HazardKind>()
+        .register_type::<ObstacleMotion>()
+        .register_type::<GameRootObject>()
+        .register_type::<Difficulty>()
+        .register_type::<Option<i32>>()
+        .register_type::<Option<Vec2>>()
+        .register_type::<Vec<Vec2>>()
+        .register_type::<StrengthCurve>()
+        .register_type::<Option<StrengthCurve>>()
+        .register_type::<Option<Rect>>()
+        .register_type::<LevelMetadata>();
+}
+
+/// Radius of the player coin, in pixels.
+pub const COIN_RADIUS: f32 = 20.0;
+/// Below this speed the coin is considered to be at rest.
+pub const REST_SPEED: f32 = 0.1;
+/// Length of one physics step, the default rate of the physics plugin.
+pub const STEP: f32 = 1.0 / 60.0;
+/// Damping of the coin outside of any [`SurfaceZone`].
+pub const COIN_LINEAR_DAMPING: f32 = 6.0;
+pub const COIN_ANGULAR_DAMPING: f32 = 9.0;
+/// Thickness of the walls around [`LevelMetadata::bounds`].
+const BOUNDARY_THICKNESS: f32 = 40.0;
+const WALL_COLOR: Color = Color::rgb(0.36, 0.25, 0.2);
+/// Obstacles that are not candles.
+const OBSTACLE_COLOR: Color = Color::rgb(0.55, 0.42, 0.3);
+
+/// Builds the physics of the level elements and moves them,
+/// shared with the headless simulation.
+pub fn level_physics_systems() -> SystemConfigs {
+    (
+        spawn_level_bounds,
+        (add_physics, add_sensors),
+        (advance_level_clock, move_obstacles).chain(),
+    )
+        .chain()
+}
+
+/// Systems acting on the coin between physics steps, in a fixed order
+/// so the headless simulation stays reproducible.
+pub fn coin_physics_systems() -> SystemConfigs {
+    (bounce_off_materials, check_hazards, apply_spin).chain()
+}
+
+/// Replaces the walls around the level whenever its bounds change.
+fn spawn_level_bounds(
+    mut commands: Commands,
+    metadata: Res<LevelMetadata>,
+    boundaries: Query<Entity, With<LevelBoundary>>,
+) {
+    if !metadata.is_changed() {
+        return;
+    }
+    for e in boundaries.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    let Some(bounds) = metadata.bounds else {
+        return;
+    };
+    let (center, size) = (bounds.center(), bounds.size());
+    let half = (size + BOUNDARY_THICKNESS) * 0.5;
+    let sides = [
+        (
+            Vec2::new(center.x, center.y + half.y),
+            Vec2::new(size.x + BOUNDARY_THICKNESS * 2.0, BOUNDARY_THICKNESS),
+        ),
+        (
+            Vec2::new(center.x, center.y - half.y),
+            Vec2::new(size.x + BOUNDARY_THICKNESS * 2.0, BOUNDARY_THICKNESS),
+        ),
+        (
+            Vec2::new(center.x - half.x, center.y),
+            Vec2::new(BOUNDARY_THICKNESS, size.y),
+        ),
+        (
+            Vec2::new(center.x + half.x, center.y),
+            Vec2::new(BOUNDARY_THICKNESS, size.y),
+        ),
+    ];
+    for (position, size) in sides {
+        commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Wall { size },
+            LevelBoundary,
+            GameRootObject,
+        ));
+    }
+}
+
+/// Adds sensor colliders to the areas the coin can enter.
+fn add_sensors(
+    mut commands: Commands,
+    zones: Query<(Entity, &SurfaceZone), Added<SurfaceZone>>,
+    hazards: Query<(Entity, &Hazard), Added<Hazard>>,
+    end_points: Query<(Entity, &EndPoint), Added<EndPoint>>,
+) {
+    for (e, zone) in zones.iter() {
+        commands.entity(e).insert((
+            Collider::cuboid(zone.size.x * 0.5, zone.size.y * 0.5),
+            Sensor,
+        ));
+    }
+
+    for (e, hazard) in hazards.iter() {
+        commands
+            .entity(e)
+            .insert((Collider::ball(hazard.radius), Sensor));
+    }
+
+    for (e, end_point) in end_points.iter() {
+        commands
+            .entity(e)
+            .insert((Collider::ball(end_point.radius - 50.0), Sensor));
+    }
+}
+
+/// Adds colliders and rigid bodies to the level elements loaded from a scene.
+/// Shared with the headless simulation, so it must not depend on rendering.
+fn add_physics(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &Obstacle), Added<Obstacle>>,
+    walls: Query<(Entity, &Wall), Added<Wall>>,
+    materials: Query<(Entity, &ObstacleMaterial), Added<ObstacleMaterial>>,
+    motions: Query<(Entity, &Transform), Added<ObstacleMotion>>,
+    start_point: Query<(Entity, &Transform), Added<PlayerSpawnPoint>>,
+) {
+    for (e, obstacle) in obstacles.iter() {
+        commands
+            .entity(e)
+            .insert(obstacle.shape.collider(obstacle.radius));
+    }
+
+    for (e, wall) in walls.iter() {
+        commands.entity(e).insert((
+            RigidBody::Fixed,
+            Collider::cuboid(wall.size.x * 0.5, wall.size.y * 0.5),
+        ));
+    }
+
+    for (e, material) in materials.iter() {
+        commands
+            .entity(e)
+            .insert((material.restitution(), material.friction()));
+    }
+
+    for (e, transform) in motions.iter() {
+        commands
+            .entity(e)
+            .insert((RigidBody::KinematicPositionBased, MotionOrigin(*transform)));
+    }
+
+    for (e, transform) in start_point.iter() {
+        commands
+            .entity(e)
+            .insert(SpawnPosition(transform.translation.truncate()))
+            .insert((
+                RigidBody::Dynamic,
+                Collider::ball(COIN_RADIUS),
+                ActiveEvents::COLLISION_EVENTS,
+                ContactForceEventThreshold(10.0),
+            ))
+            .insert(Damping {
+                linear_damping: COIN_LINEAR_DAMPING,
+                angular_damping: COIN_ANGULAR_DAMPING,
+            })
+            .insert(SensorContacts::default())
+            .insert(PlayerControlled)
+            .insert(GravityScale(0.0))
+            .insert(ReadMassProperties::default())
+            .insert(Velocity::zero())
+            .insert(ExternalImpulse {
+                impulse: Vec2::new(0.0, 0.0),
+                torque_impulse: 0.0,
+            })
+            .insert(ExternalForce::default())
+            .insert(Spin::default())
+            .insert(Restitution::coefficient(0.95));
+    }
+}
+
+/// Round obstacles are candles, the other shapes are plain meshes.
+fn add_obstacle_sprites(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    obstacles: Query<(Entity, &Transform, &Obstacle), Added<Obstacle>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    obstacles: Query<(Entity, &Transform, &Obstacle, Option<&ObstacleMaterial>), Added<Obstacle>>,
+) {
+    let candle_handle = asset_server.load("candle.png");
+    for (e, transform, obstacle, material) in obstacles.iter() {
+        let tint = material.map_or(Color::WHITE, ObstacleMaterial::tint);
+        let mut entity = commands.entity(e);
+        match (&obstacle.shape, obstacle.shape.mesh(obstacle.radius)) {
+            (ObstacleShape::Circle, _) => {
+                entity.insert(SpriteBundle {
+                    transform: *transform,
+                    texture: candle_handle.clone(),
+                    sprite: Sprite {
+                        color: tint,
+                        custom_size: Some(Vec2::splat(obstacle.radius * 2.0)),
+                        ..default()
+                    },
+                    ..default()
+                });
+            }
+            (_, Some(mesh)) => {
+                entity.insert(MaterialMesh2dBundle {
+                    transform: *transform,
+                    mesh: meshes.add(mesh).into(),
+                    material: materials.add(OBSTACLE_COLOR * tint.rgba_to_vec4()),
+                    ..default()
+                });
+            }
+            (shape, None) => {
+                let size = match shape {
+                    ObstacleShape::Box { size } => *size,
+                    _ => Vec2::splat(obstacle.radius * 2.0),
+                };
+                entity.insert(SpriteBundle {
+                    transform: *transform,
+                    sprite: Sprite {
+                        color: OBSTACLE_COLOR * tint.rgba_to_vec4(),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    ..default()
+                });
+            }
+        }
+        entity.insert(Name::new(format!("{} obstacle", obstacle.shape.name())));
+    }
+}
+
+fn add_hazard_sprites(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    hazards: Query<(Entity, &Transform, &Hazard), Added<Hazard>>,
+) {
+    for (e, transform, hazard) in hazards.iter() {
+        // Under the coin, over the surface zones.
+        let transform = transform.with_translation(transform.translation.truncate().extend(-0.5));
+        commands
+            .entity(e)
+            .insert(MaterialMesh2dBundle {
+                transform,
+                mesh: meshes.add(Circle::new(hazard.radius)).into(),
+                material: materials.add(hazard.kind.color()),
+                ..default()
+            })
+            .insert(Name::new(format!("{:?}", hazard.kind)));
+    }
+}
+
+fn add_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    walls: Query<(Entity, &Transform, &Wall), Added<Wall>>,
+    zones: Query<(Entity, &Transform, &SurfaceZone), Added<SurfaceZone>>,
     end_points: Query<(Entity, &Transform, &EndPoint), Added<EndPoint>>,
     start_point: Query<(Entity, &Transform), Added<PlayerSpawnPoint>>,
 ) {
-    let candle_handle = asset_server.load("candle.png");
-    for (e, transform, obstacle) in obstacles.iter() {
+    for (e, transform, wall) in walls.iter() {
         commands
             .entity(e)
-            .insert(Collider::ball(obstacle.radius))
             .insert(SpriteBundle {
                 transform: *transform,
-                texture: candle_handle.clone(),
                 sprite: Sprite {
-                    custom_size: Some(Vec2::splat(obstacle.radius * 2.0)),
+                    color: WALL_COLOR,
+                    custom_size: Some(wall.size),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(Name::new("Wall"));
+    }
+
+    for (e, transform, zone) in zones.iter() {
+        // Under the coin and the obstacles.
+        let transform = transform.with_translation(transform.translation.truncate().extend(-1.0));
+        commands
+            .entity(e)
+            .insert(SpriteBundle {
+                transform,
+                sprite: Sprite {
+                    color: zone.tint(),
+                    custom_size: Some(zone.size),
                     ..default()
                 },
                 ..default()
             })
-            .insert(Name::new("Candle".to_string()));
+            .insert(Name::new("Surface zone"));
     }
 
     for (e, transform, end_point) in end_points.iter() {
         commands
             .entity(e)
-            .insert((Collider::ball(end_point.radius - 50.0), Sensor))
             .insert(SpriteBundle {
                 transform: *transform,
                 texture: asset_server.load("end_circle.png"),
@@ -92,34 +467,15 @@ fn add_elements(
             .insert(Name::new("Finish point"));
     }
 
-    let radius = 20.0;
     for (e, transform) in start_point.iter() {
         commands
             .entity(e)
-            .insert((
-                RigidBody::Dynamic,
-                Collider::ball(radius),
-                ActiveEvents::COLLISION_EVENTS,
-                ContactForceEventThreshold(10.0),
-            ))
-            .insert(Damping {
-                linear_damping: 6.0,
-                angular_damping: 9.0,
-            })
             .insert(ZIndex::Global(2))
-            .insert(PlayerControlled)
-            .insert(GravityScale(0.0))
-            .insert(Velocity::zero())
-            .insert(ExternalImpulse {
-                impulse: Vec2::new(0.0, 0.0),
-                torque_impulse: 0.0,
-            })
-            .insert(Restitution::coefficient(0.95))
             .insert(SpriteBundle {
                 transform: *transform,
                 texture: asset_server.load("coin.png"),
                 sprite: Sprite {
-                    custom_size: Some(Vec2::splat(radius * 2.0)),
+                    custom_size: Some(Vec2::splat(COIN_RADIUS * 2.0)),
                     ..default()
                 },
                 ..default()
@@ -128,21 +484,41 @@ fn add_elements(
     }
 }
 
-fn reset_progress(mut progress: ResMut<GameplayProgress>) {
+fn reset_progress(
+    mut progress: ResMut<GameplayProgress>,
+    mut history: ResMut<ShotHistory>,
+    mut clock: ResMut<LevelClock>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
     progress.reset();
+    history.shots.clear();
+    *clock = LevelClock::default();
+    next_state.set(GameState::Playing);
 }
 
 fn setup_graphics(mut commands: Commands, _asset_server: Res<AssetServer>) {
     commands.spawn((Camera2dBundle::default(), MainCamera));
 }
 
-fn load_scene_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn load_scene_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    campaign: Campaign,
+    current_level: Res<CurrentLevel>,
+) {
+    let Some(level) = campaign.get(current_level.index) else {
+        error!("There is no level with index {}", current_level.index);
+        return;
+    };
+    info!("Loading level {} ({})", level.id, level.name);
+    // Levels without metadata in their scene file use the defaults.
+    commands.insert_resource(LevelMetadata::default());
     // "Spawning" a scene bundle creates a new entity and spawns new instances
     // of the given scene's entities as children of that entity.
     commands.spawn((
         DynamicSceneBundle {
             // Scenes are loaded just like any other asset.
-            scene: asset_server.load("scenes/01.scn.ron"),
+            scene: asset_server.load(&level.scene),
             ..default()
         },
         GameRootObject,
@@ -176,6 +552,34 @@ pub fn setup_world(mut commands: Commands, asset_server: Res<AssetServer>) {
         }),
         TextChanges,
     ));
+    commands
+        .spawn((
+            GameRootObject,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(15.0),
+                    right: Val::Px(15.0),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            let btn_text_style = button_text_style(&asset_server);
+            for (text, label) in [
+                ("Undo (Z)", HudButton::Undo),
+                ("Restart (R)", HudButton::Restart),
+            ] {
+                spawn_button(
+                    parent,
+                    text,
+                    label,
+                    UiRect::left(Val::Px(15.0)),
+                    &btn_text_style,
+                );
+            }
+        });
 
     commands
         .spawn(SpriteBundle {
@@ -189,39 +593,132 @@ pub fn setup_world(mut commands: Commands, asset_server: Res<AssetServer>) {
         })
         .set_parent(root)
         .insert(PointerArrow);
-    commands
-        .spawn(AudioBundle {
+}
+
+/// Music is kept out of the [`GameRootObject`] hierarchy so restarting a level does not restart it.
+fn start_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<UserSettings>,
+) {
+    commands.spawn((
+        AudioBundle {
             source: asset_server.load("snd/spinning_tavern.ogg"),
-            ..default()
-        })
-        .set_parent(root);
+            settings: settings.music_playback(),
+        },
+        Music,
+    ));
 }
 
-fn display_events(
+fn restart_on_key(actions: ActionInput, mut restart: EventWriter<RestartLevel>) {
+    if actions.just_released(Action::Restart) {
+        restart.send(RestartLevel);
+    }
+}
+
+fn undo_on_key(actions: ActionInput, mut undo: EventWriter<UndoShot>) {
+    if actions.just_released(Action::Undo) {
+        undo.send(UndoShot);
+    }
+}
+
+fn hud_buttons(
+    mut reader: EventReader<MenuButtonActivated>,
+    interaction_query: Query<&HudButton>,
+    mut restart: EventWriter<RestartLevel>,
+    mut undo: EventWriter<UndoShot>,
+) {
+    for event in reader.read() {
+        if let Ok(button_type) = interaction_query.get(**event) {
+            match *button_type {
+                HudButton::Restart => {
+                    restart.send(RestartLevel);
+                }
+                HudButton::Undo => {
+                    undo.send(UndoShot);
+                }
+            }
+        }
+    }
+}
+
+fn undo_shot(
+    mut reader: EventReader<UndoShot>,
+    mut history: ResMut<ShotHistory>,
+    mut progress: ResMut<GameplayProgress>,
+    metadata: Res<LevelMetadata>,
+    mut player: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut ExternalImpulse,
+            &mut ExternalForce,
+            &mut Spin,
+        ),
+        With<PlayerControlled>,
+    >,
+) {
+    if reader.read().count() == 0 || !metadata.can_undo(&progress) {
+        return;
+    }
+    let Ok((mut transform, mut velocity, mut impulse, mut force, mut spin)) =
+        player.get_single_mut()
+    else {
+        return;
+    };
+    let Some(snapshot) = history.shots.pop() else {
+        return;
+    };
+    *transform = snapshot.transform;
+    *velocity = snapshot.velocity;
+    *impulse = ExternalImpulse::default();
+    *force = ExternalForce::default();
+    *spin = Spin::default();
+    let moves = progress.moves;
+    let undos = progress.undos + 1;
+    *progress = snapshot.progress;
+    progress.undos = undos;
+    if metadata.undo_counts_as_move {
+        progress.moves = moves;
+    }
+}
+
+pub fn display_events(
     mut collision_events: EventReader<CollisionEvent>,
     mut ui_event: EventWriter<GameProgressEvent>,
     second_query: Query<&Sensor>,
+    end_points: Query<(), With<EndPoint>>,
+    zones: Query<&SurfaceZone>,
+    mut coin: Query<(&mut Damping, &mut SensorContacts), With<PlayerControlled>>,
     mut progress: ResMut<GameplayProgress>,
 ) {
     let mut should_send_event = false;
+    let mut contacts_changed = false;
     for collision_event in collision_events.read() {
-        match collision_event {
-            CollisionEvent::Started(e, e2, _) => {
-                let is_sensor = second_query.contains(*e) || second_query.contains(*e2);
-                if !is_sensor {
-                    progress.touches += 1;
-                } else {
-                    progress.is_inside_end_place = true;
-                }
-                should_send_event = true;
-            }
-            CollisionEvent::Stopped(e, e2, _) => {
-                let is_sensor = second_query.contains(*e) || second_query.contains(*e2);
-                if is_sensor {
-                    progress.is_inside_end_place = false;
+        let (CollisionEvent::Started(e, e2, _) | CollisionEvent::Stopped(e, e2, _)) =
+            collision_event;
+        let started = matches!(collision_event, CollisionEvent::Started(..));
+        let sensor = [*e, *e2].into_iter().find(|e| second_query.contains(*e));
+        if end_points.contains(*e) || end_points.contains(*e2) {
+            progress.is_inside_end_place = started;
+        } else if let Some(sensor) = sensor {
+            if let Ok((_, mut contacts)) = coin.get_single_mut() {
+                contacts.0.retain(|e| *e != sensor);
+                if started {
+                    contacts.0.push(sensor);
                 }
-                should_send_event = true;
+                contacts_changed = true;
             }
+        } else if started {
+            progress.touches += 1;
+        }
+        should_send_event = true;
+    }
+    if contacts_changed {
+        if let Ok((mut damping, contacts)) = coin.get_single_mut() {
+            let zone = contacts.0.iter().rev().find_map(|e| zones.get(*e).ok());
+            damping.linear_damping = zone.map_or(COIN_LINEAR_DAMPING, |z| z.linear_damping);
+            damping.angular_damping = zone.map_or(COIN_ANGULAR_DAMPING, |z| z.angular_damping);
         }
     }
     if should_send_event {
@@ -229,7 +726,11 @@ fn display_events(
     }
 }
 
-fn update_ui(mut query: Query<&mut Text, With<TextChanges>>, progress: Res<GameplayProgress>) {
+fn update_ui(
+    mut query: Query<&mut Text, With<TextChanges>>,
+    progress: Res<GameplayProgress>,
+    metadata: Res<LevelMetadata>,
+) {
     if query.is_empty() {
         return;
     }
@@ -239,39 +740,157 @@ fn update_ui(mut query: Query<&mut Text, With<TextChanges>>, progress: Res<Gamep
         "Collisions: {}\nMoves: {}",
         progress.touches, progress.moves
     );
+    if let Some(max_undos) = metadata.max_undos {
+        text.sections[0].value += &format!("\nUndos left: {}", max_undos - progress.undos);
+    }
 }
 
 fn velocity_changed(
     query: Query<&Velocity, Changed<Velocity>>,
     mut ui_event: EventWriter<GameProgressEvent>,
+    mut completed_event: EventWriter<LevelCompleted>,
+    mut next_state: ResMut<NextState<GameState>>,
     progress: Res<GameplayProgress>,
+    metadata: Res<LevelMetadata>,
 ) {
     for velocity in &query {
-        if velocity.linvel.length() < 0.1 {
+        if velocity.linvel.length() < REST_SPEED {
             ui_event.send(GameProgressEvent);
             if progress.is_inside_end_place {
-                eprintln!("WINNER!");
+                completed_event.send(LevelCompleted {
+                    progress: progress.clone(),
+                    stars: metadata.stars(&progress),
+                });
+                next_state.set(GameState::Completed);
             }
         }
     }
 }
 
+fn store_level_record(
+    mut reader: EventReader<LevelCompleted>,
+    mut records: ResMut<LevelRecords>,
+    current_level: Res<CurrentLevel>,
+    campaign: Campaign,
+) {
+    for LevelCompleted { progress, stars } in reader.read() {
+        let Some(level) = campaign.get(current_level.index) else {
+            continue;
+        };
+        records.record_completion(&level.id, progress, *stars);
+        if let Some(next_level) = campaign.get(current_level.index + 1) {
+            records.unlock(&next_level.id);
+        }
+    }
+}
+
+const ARROW_RELEASE_SECONDS: f32 = 0.25;
+/// Pixels per second the arrow flies after a shot.
+const ARROW_FLY_SPEED: f32 = 600.0;
+const CANCELLED_ARROW_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+/// Arrow tint with full spin, counter-clockwise and clockwise.
+const SPIN_LEFT_COLOR: Color = Color::rgb(0.55, 0.75, 1.0);
+const SPIN_RIGHT_COLOR: Color = Color::rgb(1.0, 0.7, 0.45);
+
+fn spin_color(spin: f32) -> Color {
+    let full = if spin > 0.0 {
+        SPIN_LEFT_COLOR
+    } else {
+        SPIN_RIGHT_COLOR
+    };
+    let t = (spin.abs() / MAX_SPIN).min(1.0);
+    let white = Color::WHITE;
+    Color::rgb(
+        white.r() + (full.r() - white.r()) * t,
+        white.g() + (full.g() - white.g()) * t,
+        white.b() + (full.b() - white.b()) * t,
+    )
+}
+
 fn arrow_display(
-    mut arrow_q: Query<&mut Transform, With<PointerArrow>>,
+    mut commands: Commands,
+    mut arrow_q: Query<(Entity, &mut Transform, &mut Sprite), With<PointerArrow>>,
     mut aim_event: EventReader<AimingEvent>,
     mut aim_event2: EventReader<AimingEndedEvent>,
     settings: Res<GameplaySettings>,
 ) {
-    if arrow_q.is_empty() {
+    let Ok((entity, mut transform, mut sprite)) = arrow_q.get_single_mut() else {
         return;
-    }
-    let mut transform = arrow_q.single_mut();
+    };
     for ev in aim_event.read() {
+        commands.entity(entity).remove::<ArrowRelease>();
+        sprite.color = spin_color(ev.spin);
         transform.translation = Vec3::new(ev.player_pos.x, ev.player_pos.y, 0.0);
         transform.scale = Vec3::splat(ev.strength / settings.max_force.y * 0.6);
         transform.rotation = Quat::from_rotation_arc_2d(Vec2::new(0.0, 1.0), ev.direction);
     }
-    for _ in aim_event2.read() {
-        transform.scale = Vec3::splat(0.0);
+    for ev in aim_event2.read() {
+        commands.entity(entity).insert(ArrowRelease {
+            shoot: ev.shoot,
+            timer: Timer::from_seconds(ARROW_RELEASE_SECONDS, TimerMode::Once),
+            start_scale: transform.scale,
+        });
+    }
+}
+
+/// In pull back mode a band is drawn from the coin to where it is pulled.
+fn draw_slingshot_band(
+    mut gizmos: Gizmos,
+    mut aim_event: EventReader<AimingEvent>,
+    settings: Res<GameplaySettings>,
+) {
+    let Some(aim) = aim_event.read().last() else {
+        return;
+    };
+    if settings.aim_mode != AimMode::PullBack {
+        return;
+    }
+    let length = settings.pull_distance(aim.strength);
+    gizmos.line_2d(
+        aim.player_pos,
+        aim.player_pos - aim.direction * length,
+        Color::hex("ECE3CE").unwrap(),
+    );
+}
+
+/// An arc around the coin, turning the way the spin curves the shot.
+fn draw_spin_indicator(mut gizmos: Gizmos, mut aim_event: EventReader<AimingEvent>) {
+    let Some(aim) = aim_event.read().last() else {
+        return;
+    };
+    if aim.spin == 0.0 {
+        return;
+    }
+    let start = aim.direction.y.atan2(aim.direction.x);
+    let sweep = aim.spin / MAX_SPIN * std::f32::consts::PI * 0.75;
+    let radius = COIN_RADIUS * 1.6;
+    let points = (0..=16).map(|i| {
+        let angle = start + sweep * i as f32 / 16.0;
+        aim.player_pos + Vec2::from_angle(angle) * radius
+    });
+    gizmos.linestrip_2d(points, spin_color(aim.spin));
+}
+
+fn animate_arrow_release(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut arrow_q: Query<(Entity, &mut Transform, &mut Sprite, &mut ArrowRelease)>,
+) {
+    for (entity, mut transform, mut sprite, mut release) in &mut arrow_q {
+        release.timer.tick(time.delta());
+        let remaining = 1.0 - release.timer.fraction();
+        if release.shoot {
+            let forward = transform.rotation * Vec3::Y;
+            transform.translation += forward * ARROW_FLY_SPEED * time.delta_seconds();
+            sprite.color.set_a(remaining);
+        } else {
+            sprite.color = CANCELLED_ARROW_COLOR;
+            transform.scale = release.start_scale * remaining;
+        }
+        if release.timer.finished() {
+            transform.scale = Vec3::ZERO;
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<ArrowRelease>();
+        }
     }
 }
@@ -0,0 +1,118 @@
+use crate::game::spin::physics_dt;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// How a kinematic obstacle moves, relative to where it was placed.
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub enum ObstacleMotion {
+    /// Back and forth along the waypoints, starting at the placed position.
+    Patrol { waypoints: Vec<Vec2>, speed: f32 },
+    /// Around `center` at `speed` radians per second, counter-clockwise when positive.
+    Orbit { center: Vec2, speed: f32 },
+    /// Turns in place at `speed` radians per second.
+    Rotate { speed: f32 },
+}
+
+impl Default for ObstacleMotion {
+    fn default() -> Self {
+        ObstacleMotion::Rotate { speed: 1.0 }
+    }
+}
+
+impl ObstacleMotion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObstacleMotion::Patrol { .. } => "Patrol",
+            ObstacleMotion::Orbit { .. } => "Orbit",
+            ObstacleMotion::Rotate { .. } => "Rotate",
+        }
+    }
+
+    pub fn variants() -> [ObstacleMotion; 3] {
+        [
+            ObstacleMotion::Patrol {
+                waypoints: vec![Vec2::new(200.0, 0.0)],
+                speed: 100.0,
+            },
+            ObstacleMotion::Orbit {
+                center: Vec2::new(0.0, -100.0),
+                speed: 1.0,
+            },
+            ObstacleMotion::Rotate { speed: 1.0 },
+        ]
+    }
+
+    /// Where the obstacle placed at `origin` is after `time` seconds of the level.
+    pub fn pose(&self, origin: &Transform, time: f32) -> Transform {
+        let start = origin.translation.truncate();
+        let mut pose = *origin;
+        match self {
+            ObstacleMotion::Patrol { waypoints, speed } => {
+                let path: Vec<Vec2> = std::iter::once(start)
+                    .chain(waypoints.iter().map(|w| start + *w))
+                    .collect();
+                let position = along_path(&path, speed * time);
+                pose.translation = position.extend(origin.translation.z);
+            }
+            ObstacleMotion::Orbit { center, speed } => {
+                let center = start + *center;
+                let position = center + Vec2::from_angle(speed * time).rotate(start - center);
+                pose.translation = position.extend(origin.translation.z);
+            }
+            ObstacleMotion::Rotate { speed } => {
+                pose.rotation = origin.rotation * Quat::from_rotation_z(speed * time);
+            }
+        }
+        pose
+    }
+}
+
+/// Point `distance` along the path, going back once the end is reached.
+fn along_path(path: &[Vec2], distance: f32) -> Vec2 {
+    let length: f32 = path.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+    if length <= f32::EPSILON {
+        return path[0];
+    }
+    let mut remaining = distance.abs() % (length * 2.0);
+    if remaining > length {
+        remaining = length * 2.0 - remaining;
+    }
+    for pair in path.windows(2) {
+        let segment = pair[0].distance(pair[1]);
+        if remaining <= segment {
+            return pair[0].lerp(pair[1], remaining / segment.max(f32::EPSILON));
+        }
+        remaining -= segment;
+    }
+    path[path.len() - 1]
+}
+
+/// Seconds of physics since the level started, advanced with the physics step
+/// instead of the frame time so moving obstacles are in the same place on every run.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct LevelClock {
+    pub elapsed: f32,
+}
+
+/// Placed transform of a moving obstacle, its motion is relative to it.
+#[derive(Component)]
+pub struct MotionOrigin(pub Transform);
+
+pub fn advance_level_clock(
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    mut clock: ResMut<LevelClock>,
+) {
+    clock.elapsed += physics_dt(&config, &time);
+}
+
+pub fn move_obstacles(
+    clock: Res<LevelClock>,
+    mut obstacles: Query<(&ObstacleMotion, &MotionOrigin, &mut Transform)>,
+) {
+    for (motion, origin, mut transform) in &mut obstacles {
+        transform.set_if_neq(motion.pose(&origin.0, clock.elapsed));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::game::components::GameplayProgress;
+use crate::save;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+pub const RECORDS_SAVE_KEY: &str = "progress";
+
+/// Best results achieved by the player on a single level.
+#[derive(Debug, Clone, Default, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelRecord {
+    pub unlocked: bool,
+    pub completed: bool,
+    pub best_moves: Option<i32>,
+    pub fewest_touches: Option<i32>,
+    pub best_stars: u8,
+}
+
+/// Player results for every level, keyed by the level id from the manifest.
+#[derive(Resource, Reflect, Default, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct LevelRecords {
+    pub levels: HashMap<String, LevelRecord>,
+}
+
+impl LevelRecords {
+    pub fn get(&self, id: &str) -> Option<&LevelRecord> {
+        self.levels.get(id)
+    }
+
+    /// First level of the campaign is always playable.
+    pub fn is_unlocked(&self, index: usize, id: &str) -> bool {
+        index == 0 || self.get(id).is_some_and(|record| record.unlocked)
+    }
+
+    pub fn unlock(&mut self, id: &str) {
+        self.levels.entry(id.to_string()).or_default().unlocked = true;
+    }
+
+    pub fn record_completion(&mut self, id: &str, progress: &GameplayProgress, stars: u8) {
+        let record = self.levels.entry(id.to_string()).or_default();
+        record.unlocked = true;
+        record.completed = true;
+        record.best_stars = record.best_stars.max(stars);
+        record.best_moves = Some(
+            record
+                .best_moves
+                .map_or(progress.moves, |moves| moves.min(progress.moves)),
+        );
+        record.fewest_touches = Some(
+            record
+                .fewest_touches
+                .map_or(progress.touches, |touches| touches.min(progress.touches)),
+        );
+    }
+}
+
+pub fn save_records(records: Res<LevelRecords>) {
+    if records.is_changed() && !records.is_added() {
+        save::store(RECORDS_SAVE_KEY, &*records);
+    }
+}
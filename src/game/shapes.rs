@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_rapier2d::prelude::Collider;
+
+/// Outline of an [`crate::game::components::Obstacle`], sized by its radius
+/// where the shape needs one and rotated with its transform.
+#[derive(Reflect, Clone, Debug, PartialEq, Default)]
+pub enum ObstacleShape {
+    #[default]
+    Circle,
+    Box {
+        size: Vec2,
+    },
+    /// Two half circles of the obstacle radius, `length` apart along the y axis.
+    Capsule {
+        length: f32,
+    },
+    /// Convex polygon around the points, relative to the obstacle position.
+    Polygon(Vec<Vec2>),
+}
+
+impl ObstacleShape {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ObstacleShape::Circle => "Circle",
+            ObstacleShape::Box { .. } => "Box",
+            ObstacleShape::Capsule { .. } => "Capsule",
+            ObstacleShape::Polygon(_) => "Polygon",
+        }
+    }
+
+    /// Each shape with a size close to a circle of `radius`, for the editor.
+    pub fn variants(radius: f32) -> [ObstacleShape; 4] {
+        [
+            ObstacleShape::Circle,
+            ObstacleShape::Box {
+                size: Vec2::splat(radius * 2.0),
+            },
+            ObstacleShape::Capsule {
+                length: radius * 2.0,
+            },
+            ObstacleShape::Polygon(
+                (0..3)
+                    .map(|i| Vec2::from_angle(std::f32::consts::TAU * i as f32 / 3.0) * radius)
+                    .collect(),
+            ),
+        ]
+    }
+
+    pub fn collider(&self, radius: f32) -> Collider {
+        match self {
+            ObstacleShape::Circle => Collider::ball(radius),
+            ObstacleShape::Box { size } => Collider::cuboid(size.x * 0.5, size.y * 0.5),
+            ObstacleShape::Capsule { length } => Collider::capsule_y(length * 0.5, radius),
+            // Less than three points or points on a line have no hull.
+            ObstacleShape::Polygon(points) => {
+                Collider::convex_hull(points).unwrap_or_else(|| Collider::ball(radius))
+            }
+        }
+    }
+
+    /// Mesh for the shapes that are not drawn with a sprite.
+    pub fn mesh(&self, radius: f32) -> Option<Mesh> {
+        match self {
+            ObstacleShape::Circle | ObstacleShape::Box { .. } => None,
+            ObstacleShape::Capsule { length } => Some(Capsule2d::new(radius, *length).into()),
+            ObstacleShape::Polygon(points) => {
+                let outline = convex_outline(points);
+                if outline.is_empty() {
+                    Some(Circle::new(radius).into())
+                } else {
+                    Some(polygon_mesh(&outline))
+                }
+            }
+        }
+    }
+
+    pub fn draw(&self, gizmos: &mut Gizmos, radius: f32, position: Vec2, angle: f32, color: Color) {
+        match self {
+            ObstacleShape::Circle => {
+                gizmos.circle_2d(position, radius, color);
+            }
+            ObstacleShape::Box { size } => gizmos.rect_2d(position, angle, *size, color),
+            ObstacleShape::Capsule { length } => {
+                gizmos.primitive_2d(Capsule2d::new(radius, *length), position, angle, color);
+            }
+            ObstacleShape::Polygon(points) => {
+                let outline = convex_outline(points);
+                if outline.is_empty() {
+                    gizmos.circle_2d(position, radius, color);
+                    return;
+                }
+                let rotation = Vec2::from_angle(angle);
+                let closed = outline.iter().chain(outline.first());
+                gizmos.linestrip_2d(closed.map(|p| position + rotation.rotate(*p)), color);
+            }
+        }
+    }
+}
+
+/// Corners of the hull the collider is built from, counter-clockwise.
+/// Empty when the points have no hull and the collider falls back to a circle.
+fn convex_outline(points: &[Vec2]) -> Vec<Vec2> {
+    let Some(hull) = Collider::convex_hull(points) else {
+        return Vec::new();
+    };
+    let outline = hull
+        .as_convex_polygon()
+        .map(|polygon| polygon.points().collect());
+    outline.unwrap_or_default()
+}
+
+/// Triangle fan from the center of the outline.
+fn polygon_mesh(outline: &[Vec2]) -> Mesh {
+    let center = outline.iter().sum::<Vec2>() / outline.len() as f32;
+    let positions: Vec<[f32; 3]> = std::iter::once(center)
+        .chain(outline.iter().copied())
+        .map(|p| [p.x, p.y, 0.0])
+        .collect();
+    let count = outline.len() as u32;
+    let indices = (0..count)
+        .flat_map(|i| [0, i + 1, (i + 1) % count + 1])
+        .collect();
+    let vertices = positions.len();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; vertices])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; vertices])
+    .with_inserted_indices(Indices::U32(indices))
+}
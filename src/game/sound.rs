@@ -0,0 +1,93 @@
+use crate::input::AimingEndedEvent;
+use bevy::audio::Source;
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 44_100;
+const SHOT_SECONDS: f32 = 0.15;
+
+/// Short falling whistle played when the coin is shot.
+/// Generated while playing, so it needs no sound file.
+#[derive(Asset, TypePath, Clone, Copy, Debug)]
+pub struct ShotSound {
+    /// Pitch at the start and at the end of the sound, in hertz.
+    pub from: f32,
+    pub to: f32,
+}
+
+impl Default for ShotSound {
+    fn default() -> Self {
+        Self {
+            from: 900.0,
+            to: 300.0,
+        }
+    }
+}
+
+pub struct ShotDecoder {
+    sound: ShotSound,
+    sample: u32,
+    phase: f32,
+}
+
+impl Iterator for ShotDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let progress = self.sample as f32 / SAMPLE_RATE as f32 / SHOT_SECONDS;
+        if progress >= 1.0 {
+            return None;
+        }
+        self.sample += 1;
+        let frequency = self.sound.from + (self.sound.to - self.sound.from) * progress;
+        self.phase = (self.phase + frequency / SAMPLE_RATE as f32).fract();
+        let fade = (1.0 - progress).powi(2);
+        Some((self.phase * TAU).sin() * fade * 0.5)
+    }
+}
+
+impl Source for ShotDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(SHOT_SECONDS))
+    }
+}
+
+impl Decodable for ShotSound {
+    type DecoderItem = f32;
+    type Decoder = ShotDecoder;
+
+    fn decoder(&self) -> ShotDecoder {
+        ShotDecoder {
+            sound: *self,
+            sample: 0,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Cancelled aims end without a sound.
+pub fn play_shot_sound(
+    mut commands: Commands,
+    mut ended: EventReader<AimingEndedEvent>,
+    mut sounds: ResMut<Assets<ShotSound>>,
+) {
+    for _ in ended.read().filter(|ev| ev.shoot) {
+        commands.spawn(AudioSourceBundle {
+            source: sounds.add(ShotSound::default()),
+            settings: PlaybackSettings::DESPAWN,
+        });
+    }
+}
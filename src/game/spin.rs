@@ -0,0 +1,67 @@
+use crate::game::REST_SPEED;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Spin is a fraction from -1 to 1, positive curves the coin counter-clockwise.
+pub const MAX_SPIN: f32 = 1.0;
+/// Radians per second the direction of a coin with full spin turns.
+const SPIN_CURVE: f32 = 3.0;
+/// Fraction of the spin lost per second.
+const SPIN_DECAY: f32 = 1.5;
+
+/// Side spin of the player coin, set by the shot and slowly lost while it moves.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Spin(pub f32);
+
+/// Magnus like acceleration, sideways to the motion of the coin.
+pub fn spin_acceleration(velocity: Vec2, spin: f32) -> Vec2 {
+    velocity.perp() * spin * SPIN_CURVE
+}
+
+pub fn decay_spin(spin: f32, dt: f32) -> f32 {
+    spin * (-SPIN_DECAY * dt).exp()
+}
+
+/// Length of the next physics step, so the spin decays the same with a fixed timestep.
+/// Zero while the physics is paused.
+pub fn physics_dt(config: &RapierConfiguration, time: &Time) -> f32 {
+    if !config.physics_pipeline_active {
+        return 0.0;
+    }
+    match config.timestep_mode {
+        TimestepMode::Fixed { dt, .. } => dt,
+        TimestepMode::Variable {
+            max_dt, time_scale, ..
+        } => time.delta_seconds().min(max_dt) * time_scale,
+        TimestepMode::Interpolated { dt, time_scale, .. } => dt * time_scale,
+    }
+}
+
+/// Pushes spinning coins sideways.
+pub fn apply_spin(
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    mut coins: Query<(
+        &mut Spin,
+        &mut ExternalForce,
+        &ExternalImpulse,
+        &Velocity,
+        &ReadMassProperties,
+    )>,
+) {
+    let dt = physics_dt(&config, &time);
+    for (mut spin, mut force, impulse, velocity, mass) in &mut coins {
+        if spin.0 == 0.0 {
+            continue;
+        }
+        // A shot that was not applied yet has no speed.
+        if velocity.linvel.length() < REST_SPEED && impulse.impulse == Vec2::ZERO {
+            spin.0 = 0.0;
+            force.force = Vec2::ZERO;
+            continue;
+        }
+        force.force = spin_acceleration(velocity.linvel, spin.0) * mass.get().mass;
+        spin.0 = decay_spin(spin.0, dt);
+    }
+}
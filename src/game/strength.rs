@@ -0,0 +1,292 @@
+use crate::game::components::{GameplaySettings, LevelMetadata};
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use thiserror::Error;
+
+/// Maps how far the pointer is pulled to how strong the shot is,
+/// both as fractions from 0 to 1.
+#[derive(Reflect, Clone, Debug, PartialEq, Default)]
+pub enum StrengthCurve {
+    #[default]
+    Linear,
+    /// More precision for soft shots.
+    Quadratic,
+    /// Like quadratic, with the bend set by `steepness`.
+    Exponential { steepness: f32 },
+    /// Points of (pull, strength) joined by straight lines.
+    /// Sorted by pull when the settings are resolved, see [`sort_curve_keyframes`].
+    Keyframes(Vec<Vec2>),
+    /// Keyframes from a `.curve.ron` file, relative to the assets folder.
+    /// Linear until the file is loaded.
+    Asset(String),
+}
+
+impl StrengthCurve {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrengthCurve::Linear => "Linear",
+            StrengthCurve::Quadratic => "Quadratic",
+            StrengthCurve::Exponential { .. } => "Exponential",
+            StrengthCurve::Keyframes(_) => "Keyframes",
+            StrengthCurve::Asset(_) => "Asset",
+        }
+    }
+
+    pub fn has_sorted_keyframes(&self) -> bool {
+        match self {
+            StrengthCurve::Keyframes(keys) => keys.windows(2).all(|pair| pair[0].x <= pair[1].x),
+            _ => true,
+        }
+    }
+
+    /// Sampling expects the keyframes in order of pull.
+    pub fn sort_keyframes(&mut self) {
+        if let StrengthCurve::Keyframes(keys) = self {
+            sort_keys(keys);
+        }
+    }
+
+    pub fn sample(&self, pull: f32) -> f32 {
+        let t = pull.clamp(0.0, 1.0);
+        match self {
+            StrengthCurve::Linear | StrengthCurve::Asset(_) => t,
+            StrengthCurve::Quadratic => t * t,
+            StrengthCurve::Exponential { steepness } if steepness.abs() > f32::EPSILON => {
+                ((steepness * t).exp() - 1.0) / (steepness.exp() - 1.0)
+            }
+            StrengthCurve::Exponential { .. } => t,
+            StrengthCurve::Keyframes(keys) => sample_keyframes(keys, t),
+        }
+    }
+
+    /// Pull that gives `strength`, the curves are expected to only go up.
+    pub fn inverse(&self, strength: f32) -> f32 {
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..24 {
+            let middle = (low + high) * 0.5;
+            if self.sample(middle) < strength {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        (low + high) * 0.5
+    }
+}
+
+fn sort_keys(keys: &mut [Vec2]) {
+    keys.sort_by(|a, b| a.x.total_cmp(&b.x));
+}
+
+fn sample_keyframes(keys: &[Vec2], t: f32) -> f32 {
+    let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+        return t;
+    };
+    if t <= first.x {
+        return first.y;
+    }
+    keys.windows(2)
+        .find(|pair| t <= pair[1].x)
+        .map_or(last.y, |pair| {
+            let span = pair[1].x - pair[0].x;
+            if span <= 0.0 {
+                pair[1].y
+            } else {
+                pair[0].y + (pair[1].y - pair[0].y) * (t - pair[0].x) / span
+            }
+        })
+}
+
+/// Keyframes for [`StrengthCurve::Asset`], a RON list of `(pull, strength)` pairs.
+#[derive(Asset, TypePath, Debug)]
+pub struct StrengthCurveAsset {
+    pub keyframes: Vec<Vec2>,
+}
+
+impl StrengthCurveAsset {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ron::error::SpannedError> {
+        let points: Vec<(f32, f32)> = ron::de::from_bytes(bytes)?;
+        let mut keyframes: Vec<Vec2> = points.into_iter().map(Vec2::from).collect();
+        sort_keys(&mut keyframes);
+        Ok(Self { keyframes })
+    }
+}
+
+#[derive(Default)]
+pub struct StrengthCurveLoader;
+
+#[derive(Debug, Error)]
+pub enum StrengthCurveLoaderError {
+    #[error("Could not read strength curve: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse strength curve: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for StrengthCurveLoader {
+    type Asset = StrengthCurveAsset;
+    type Settings = ();
+    type Error = StrengthCurveLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(StrengthCurveAsset::from_bytes(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["curve.ron"]
+    }
+}
+
+/// The defaults with the overrides of the level applied.
+pub fn level_settings(metadata: &LevelMetadata, current: &GameplaySettings) -> GameplaySettings {
+    let defaults = GameplaySettings::default();
+    let mut curve = metadata.strength_curve.clone().unwrap_or(defaults.curve);
+    curve.sort_keyframes();
+    GameplaySettings {
+        min_force: metadata.min_force.unwrap_or(defaults.min_force),
+        max_force: metadata.max_force.unwrap_or(defaults.max_force),
+        curve,
+        loaded_curve: None,
+        aim_mode: current.aim_mode,
+    }
+}
+
+pub fn apply_level_overrides(metadata: Res<LevelMetadata>, mut settings: ResMut<GameplaySettings>) {
+    if metadata.is_changed() {
+        let level = level_settings(&metadata, &settings);
+        *settings = level;
+    }
+}
+
+/// Keyframes edited in the inspector can be in any order.
+pub fn sort_curve_keyframes(mut settings: ResMut<GameplaySettings>) {
+    if !settings.curve.has_sorted_keyframes() {
+        settings.curve.sort_keyframes();
+    }
+}
+
+/// Loads the keyframes of a [`StrengthCurve::Asset`] into [`GameplaySettings::loaded_curve`],
+/// keeping the path in [`GameplaySettings::curve`].
+pub fn resolve_curve_asset(
+    asset_server: Res<AssetServer>,
+    curves: Res<Assets<StrengthCurveAsset>>,
+    mut settings: ResMut<GameplaySettings>,
+    mut handle: Local<Option<(String, Handle<StrengthCurveAsset>)>>,
+) {
+    let StrengthCurve::Asset(path) = &settings.curve else {
+        return;
+    };
+    if handle.as_ref().is_none_or(|(loaded, _)| loaded != path) {
+        let path = path.clone();
+        *handle = Some((path.clone(), asset_server.load(path)));
+        settings.loaded_curve = None;
+    }
+    let Some(curve) = handle.as_ref().and_then(|(_, h)| curves.get(h)) else {
+        return;
+    };
+    let loaded = StrengthCurve::Keyframes(curve.keyframes.clone());
+    if settings.loaded_curve.as_ref() != Some(&loaded) {
+        settings.loaded_curve = Some(loaded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn curves_go_from_zero_to_one() {
+        for curve in [
+            StrengthCurve::Linear,
+            StrengthCurve::Quadratic,
+            StrengthCurve::Exponential { steepness: 3.0 },
+            StrengthCurve::Exponential { steepness: 0.0 },
+            StrengthCurve::Keyframes(vec![Vec2::ZERO, Vec2::new(0.5, 0.2), Vec2::ONE]),
+            StrengthCurve::Asset("curves/soft.curve.ron".to_string()),
+        ] {
+            assert_close(curve.sample(0.0), 0.0);
+            assert_close(curve.sample(1.0), 1.0);
+            // Pulls outside the range are clamped.
+            assert_close(curve.sample(-1.0), 0.0);
+            assert_close(curve.sample(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn quadratic_gives_soft_shots_more_precision() {
+        assert_close(StrengthCurve::Quadratic.sample(0.5), 0.25);
+        assert!(StrengthCurve::Exponential { steepness: 3.0 }.sample(0.5) < 0.5);
+    }
+
+    #[test]
+    fn inverse_finds_the_pull() {
+        for curve in [
+            StrengthCurve::Linear,
+            StrengthCurve::Quadratic,
+            StrengthCurve::Exponential { steepness: 3.0 },
+            StrengthCurve::Keyframes(vec![Vec2::ZERO, Vec2::new(0.5, 0.2), Vec2::ONE]),
+        ] {
+            for pull in [0.1, 0.3, 0.5, 0.9] {
+                assert_close(curve.inverse(curve.sample(pull)), pull);
+            }
+        }
+    }
+
+    #[test]
+    fn keyframes_are_joined_by_lines() {
+        let curve = StrengthCurve::Keyframes(vec![Vec2::new(0.2, 0.1), Vec2::new(0.6, 0.5)]);
+        assert_close(curve.sample(0.0), 0.1);
+        assert_close(curve.sample(0.4), 0.3);
+        assert_close(curve.sample(1.0), 0.5);
+        // No keyframes behaves like a linear curve.
+        assert_close(StrengthCurve::Keyframes(Vec::new()).sample(0.3), 0.3);
+    }
+
+    #[test]
+    fn unsorted_keyframes_are_sorted() {
+        let mut curve = StrengthCurve::Keyframes(vec![Vec2::ONE, Vec2::ZERO, Vec2::new(0.5, 0.2)]);
+        assert!(!curve.has_sorted_keyframes());
+        curve.sort_keyframes();
+        assert!(curve.has_sorted_keyframes());
+        assert_close(curve.sample(0.5), 0.2);
+    }
+
+    #[test]
+    fn curve_files_are_sorted_on_load() {
+        let curve =
+            StrengthCurveAsset::from_bytes(b"[(1.0, 1.0), (0.0, 0.0), (0.5, 0.2)]").unwrap();
+        assert_eq!(
+            curve.keyframes,
+            vec![Vec2::ZERO, Vec2::new(0.5, 0.2), Vec2::ONE]
+        );
+    }
+
+    #[test]
+    fn asset_curve_keeps_its_path_once_loaded() {
+        let settings = GameplaySettings {
+            curve: StrengthCurve::Asset("curves/soft.curve.ron".to_string()),
+            loaded_curve: Some(StrengthCurve::Quadratic),
+            ..default()
+        };
+        assert_eq!(settings.active_curve(), &StrengthCurve::Quadratic);
+        let linear = GameplaySettings {
+            loaded_curve: Some(StrengthCurve::Quadratic),
+            ..default()
+        };
+        assert_eq!(linear.active_curve(), &StrengthCurve::Linear);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::game::components::{LevelMetadata, PlayerControlled, SurfaceZone};
+use crate::game::materials::ObstacleMaterial;
+use crate::game::spin::{decay_spin, spin_acceleration};
+use crate::game::{COIN_LINEAR_DAMPING, STEP};
+use crate::input::AimingEvent;
+use crate::states::settings::UserSettings;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+const MAX_STEPS: usize = 300;
+/// Below this speed the predicted path ends.
+const STOP_SPEED: f32 = 5.0;
+/// Distance the coin is moved off a surface after a bounce, so the next cast
+/// does not report the same contact again.
+const SKIN: f32 = 0.01;
+
+/// Physical properties of the coin needed to predict its motion.
+pub struct CoinBody<'a> {
+    pub entity: Entity,
+    pub collider: &'a Collider,
+    /// Damping outside of surface zones.
+    pub linear_damping: f32,
+    pub restitution: f32,
+}
+
+/// How the coin is shot.
+pub struct Launch {
+    pub start: Vec2,
+    pub velocity: Vec2,
+    pub spin: f32,
+}
+
+/// What the coin bounces off.
+#[derive(Default)]
+pub struct Surface {
+    pub restitution: Option<Restitution>,
+    /// Speed added away from the surface, see [`ObstacleMaterial::bounce`].
+    pub kick: f32,
+}
+
+/// Predicts where the coin goes after `launch`, ending the path when it stops
+/// or right after `max_bounces` collisions. `damping_at` gives the damping of
+/// the surface zone at a point, if there is one.
+pub fn predict_path(
+    context: &RapierContext,
+    launch: Launch,
+    coin: &CoinBody,
+    max_bounces: usize,
+    surface_of: impl Fn(Entity) -> Surface,
+    damping_at: impl Fn(Vec2) -> Option<f32>,
+) -> Vec<Vec2> {
+    let filter = QueryFilter::new()
+        .exclude_rigid_body(coin.entity)
+        .exclude_sensors();
+    let mut position = launch.start;
+    let mut velocity = launch.velocity;
+    let mut spin = launch.spin;
+    let mut bounces = 0;
+    let mut points = vec![position];
+    for _ in 0..MAX_STEPS {
+        if velocity.length() < STOP_SPEED {
+            break;
+        }
+        let motion = velocity * STEP;
+        match context.cast_shape(position, 0.0, motion, coin.collider, 1.0, true, filter) {
+            Some((entity, toi)) => {
+                let Some(details) = toi.details else {
+                    break;
+                };
+                // The first normal is on the hit collider, in world space, pointing to the coin.
+                let normal = details.normal1.normalize_or_zero();
+                // A touching shape is reported at zero time of impact whichever way
+                // the coin moves, which is not a hit when it already moves away.
+                if toi.toi <= 0.0 && velocity.dot(normal) >= 0.0 {
+                    position += motion;
+                    points.push(position);
+                } else {
+                    position += motion * toi.toi;
+                    points.push(position);
+                    bounces += 1;
+                    if bounces > max_bounces {
+                        break;
+                    }
+                    let surface = surface_of(entity);
+                    let restitution = combined_restitution(coin.restitution, surface.restitution);
+                    velocity -= (1.0 + restitution) * velocity.dot(normal) * normal;
+                    velocity += velocity.normalize_or_zero() * surface.kick;
+                    position += normal * SKIN;
+                }
+            }
+            None => {
+                position += motion;
+                points.push(position);
+            }
+        }
+        velocity += spin_acceleration(velocity, spin) * STEP;
+        spin = decay_spin(spin, STEP);
+        let damping = damping_at(position).unwrap_or(coin.linear_damping);
+        velocity *= 1.0 / (1.0 + STEP * damping);
+    }
+    points
+}
+
+/// Restitution of a bounce, combined like rapier does. The coin uses the default
+/// average rule, which the rule of any other collider takes priority over.
+fn combined_restitution(coin: f32, other: Option<Restitution>) -> f32 {
+    let Some(other) = other else {
+        return coin * 0.5;
+    };
+    let (a, b) = (coin, other.coefficient);
+    match other.combine_rule {
+        CoefficientCombineRule::Average => (a + b) * 0.5,
+        CoefficientCombineRule::Min => a.min(b),
+        CoefficientCombineRule::Multiply => a * b,
+        CoefficientCombineRule::Max => a.max(b),
+    }
+}
+
+pub fn draw_trajectory(
+    mut gizmos: Gizmos,
+    mut aim_events: EventReader<AimingEvent>,
+    rapier_context: Res<RapierContext>,
+    player: Query<(Entity, &Collider, &Restitution, &ReadMassProperties), With<PlayerControlled>>,
+    surfaces: Query<(
+        Option<&Restitution>,
+        Option<&ObstacleMaterial>,
+        Option<&SurfaceZone>,
+    )>,
+    metadata: Res<LevelMetadata>,
+    settings: Res<UserSettings>,
+) {
+    let Some(aim) = aim_events.read().last() else {
+        return;
+    };
+    let Some(max_bounces) = metadata.difficulty.preview_bounces() else {
+        return;
+    };
+    if !settings.show_trajectory {
+        return;
+    }
+    let Ok((entity, collider, restitution, mass)) = player.get_single() else {
+        return;
+    };
+    let mass = mass.get().mass;
+    if mass <= 0.0 {
+        return;
+    }
+    let coin = CoinBody {
+        entity,
+        collider,
+        // The damping of the coin is the one of the zone it rests in.
+        linear_damping: COIN_LINEAR_DAMPING,
+        restitution: restitution.coefficient,
+    };
+    let launch = Launch {
+        start: aim.player_pos,
+        velocity: aim.direction * aim.strength / mass,
+        spin: aim.spin,
+    };
+    let points = predict_path(
+        &rapier_context,
+        launch,
+        &coin,
+        max_bounces,
+        |e| {
+            let Ok((restitution, material, _)) = surfaces.get(e) else {
+                return Surface::default();
+            };
+            Surface {
+                restitution: restitution.copied(),
+                kick: material.map_or(0.0, |m| m.bounce / mass),
+            }
+        },
+        |point| {
+            let mut damping = None;
+            rapier_context.intersections_with_point(point, QueryFilter::new(), |e| {
+                damping = surfaces
+                    .get(e)
+                    .ok()
+                    .and_then(|(_, _, zone)| zone)
+                    .map(|zone| zone.linear_damping);
+                damping.is_none()
+            });
+            damping
+        },
+    );
+    gizmos.linestrip_2d(points, Color::rgba(0.9, 0.9, 0.8, 0.6));
+}
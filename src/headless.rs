@@ -0,0 +1,323 @@
+//! Runs levels without a window, for tools and automated checks.
+//!
+//! The simulation uses the same scene files, physics setup and shot impulse
+//! as the game, with a fixed physics step so the results are reproducible.
+
+use crate::consts::PIXELS_PER_METER;
+use crate::game::components::*;
+use crate::game::motion::LevelClock;
+use crate::game::spin::Spin;
+use crate::game::strength::{level_settings, StrengthCurve, StrengthCurveAsset};
+use crate::game::{
+    coin_physics_systems, display_events, level_physics_systems, register_level_types,
+    GameProgressEvent, REST_SPEED, STEP,
+};
+use crate::input::PlayerCoin;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::scene::serde::SceneDeserializer;
+use bevy::scene::{DynamicScene, ScenePlugin, SceneSpawnError};
+use bevy_rapier2d::prelude::*;
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// Asset paths in level files are relative to this folder.
+const ASSETS_DIR: &str = "assets";
+/// A shot that did not settle after this many steps is cut short.
+const MAX_SETTLE_STEPS: usize = 60 * 60;
+
+/// One scripted shot.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Shot {
+    /// Direction in degrees, counter-clockwise from the positive x axis.
+    pub angle: f32,
+    /// Impulse applied to the coin, the same value the aiming input produces.
+    pub strength: f32,
+    /// Side spin from -1 to 1, positive curves the shot counter-clockwise.
+    #[serde(default)]
+    pub spin: f32,
+    /// Level time in seconds to wait for before shooting, to time shots past moving obstacles.
+    /// Without it the shot is taken as soon as the coin is at rest.
+    #[serde(default)]
+    pub at: Option<f32>,
+}
+
+impl Shot {
+    pub fn direction(&self) -> Vec2 {
+        Vec2::from_angle(self.angle.to_radians())
+    }
+}
+
+/// State of a [`Simulation`] while the coin is at rest, to try several shots from it.
+pub struct SimulationSnapshot {
+    transform: Transform,
+    velocity: Velocity,
+    damping: Damping,
+    contacts: SensorContacts,
+    progress: GameplayProgress,
+    history: Vec<ShotSnapshot>,
+    clock: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationReport {
+    /// The coin came to rest inside the end place.
+    pub solved: bool,
+    pub moves: i32,
+    pub touches: i32,
+    pub position: Vec2,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("Could not read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not spawn scene: {0}")]
+    Spawn(#[from] SceneSpawnError),
+    #[error("The level has no player spawn point")]
+    NoPlayer,
+}
+
+/// Physics of the game without rendering, audio or input.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        register_level_types(app);
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            ScenePlugin,
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER),
+        ))
+        // Rapier looks for meshes to build colliders from.
+        .init_asset::<Mesh>()
+        .init_resource::<GameplaySettings>()
+        .init_resource::<GameplayProgress>()
+        .init_resource::<ShotHistory>()
+        .init_resource::<LevelMetadata>()
+        .init_resource::<LevelClock>()
+        .add_event::<GameProgressEvent>()
+        .add_systems(Update, (level_physics_systems(), coin_physics_systems()))
+        .add_systems(PostUpdate, display_events);
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: STEP,
+            substeps: 1,
+        };
+    }
+}
+
+/// A level loaded into a headless app, advanced one physics step per update.
+pub struct Simulation {
+    app: App,
+}
+
+impl Simulation {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        Self::from_scene_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_scene_str(scene: &str) -> Result<Self, SimulationError> {
+        let mut app = App::new();
+        app.add_plugins(HeadlessPlugin);
+
+        let registry = app.world.resource::<AppTypeRegistry>().clone();
+        let mut deserializer = ron::de::Deserializer::from_str(scene)?;
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|e| deserializer.span_error(e))?;
+        write_scene(&mut app.world, &scene)?;
+        apply_level_settings(&mut app.world);
+
+        let mut simulation = Self { app };
+        // Lets rapier create the bodies before the first shot.
+        simulation.step();
+        if simulation.coin().is_none() {
+            return Err(SimulationError::NoPlayer);
+        }
+        Ok(simulation)
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    pub fn step(&mut self) {
+        self.app.update();
+    }
+
+    /// Seconds of physics since the level started, what moving obstacles follow.
+    pub fn clock(&self) -> f32 {
+        self.app.world.resource::<LevelClock>().elapsed
+    }
+
+    /// Shoots the coin exactly like the player does, then steps until it stops.
+    pub fn shoot(&mut self, shot: Shot) {
+        if let Some(at) = shot.at {
+            while self.clock() < at {
+                self.step();
+            }
+        }
+        self.app
+            .world
+            .run_system_once_with(shot, |In(shot): In<Shot>, mut coin: PlayerCoin| {
+                coin.shoot(shot.direction(), shot.strength, shot.spin);
+            });
+        self.settle();
+    }
+
+    /// Steps until the coin comes to rest.
+    pub fn settle(&mut self) {
+        // The impulse is only applied on the next step.
+        self.step();
+        for _ in 0..MAX_SETTLE_STEPS {
+            if self.is_at_rest() {
+                break;
+            }
+            self.step();
+        }
+    }
+
+    pub fn is_at_rest(&self) -> bool {
+        self.coin()
+            .is_none_or(|(_, velocity)| velocity.linvel.length() < REST_SPEED)
+    }
+
+    pub fn report(&self) -> SimulationReport {
+        let progress = self.app.world.resource::<GameplayProgress>();
+        SimulationReport {
+            solved: progress.is_inside_end_place && self.is_at_rest(),
+            moves: progress.moves,
+            touches: progress.touches,
+            position: self.coin().map_or(Vec2::ZERO, |(transform, _)| {
+                transform.translation.truncate()
+            }),
+        }
+    }
+
+    pub fn snapshot(&mut self) -> SimulationSnapshot {
+        let world = &mut self.app.world;
+        let mut coin = world.query_filtered::<
+            (&Transform, &Velocity, &Damping, &SensorContacts),
+            With<PlayerControlled>,
+        >();
+        let (transform, velocity, damping, contacts) = coin.single(world);
+        SimulationSnapshot {
+            transform: *transform,
+            velocity: *velocity,
+            damping: *damping,
+            contacts: contacts.clone(),
+            progress: world.resource::<GameplayProgress>().clone(),
+            history: world.resource::<ShotHistory>().shots.clone(),
+            clock: world.resource::<LevelClock>().elapsed,
+        }
+    }
+
+    /// Puts the coin back like [`crate::game`] undoes a shot, and rewinds the level clock.
+    /// Rapier keeps its contacts, so collisions reported right after this may differ
+    /// slightly from playing the same shots from the start.
+    pub fn restore(&mut self, snapshot: &SimulationSnapshot) {
+        let world = &mut self.app.world;
+        let mut coin = world.query_filtered::<CoinState, With<PlayerControlled>>();
+        let (
+            mut transform,
+            mut velocity,
+            mut impulse,
+            mut force,
+            mut spin,
+            mut damping,
+            mut contacts,
+        ) = coin.single_mut(world);
+        *transform = snapshot.transform;
+        *velocity = snapshot.velocity;
+        *impulse = ExternalImpulse::default();
+        *force = ExternalForce::default();
+        *spin = Spin::default();
+        *damping = snapshot.damping;
+        *contacts = snapshot.contacts.clone();
+        *world.resource_mut::<GameplayProgress>() = snapshot.progress.clone();
+        world.resource_mut::<ShotHistory>().shots = snapshot.history.clone();
+        world.resource_mut::<LevelClock>().elapsed = snapshot.clock;
+    }
+
+    fn coin(&self) -> Option<(&Transform, &Velocity)> {
+        let world = &self.app.world;
+        let entity = world
+            .iter_entities()
+            .find(|e| e.contains::<PlayerControlled>())?;
+        Some((entity.get::<Transform>()?, entity.get::<Velocity>()?))
+    }
+}
+
+type CoinState<'a> = (
+    &'a mut Transform,
+    &'a mut Velocity,
+    &'a mut ExternalImpulse,
+    &'a mut ExternalForce,
+    &'a mut Spin,
+    &'a mut Damping,
+    &'a mut SensorContacts,
+);
+
+/// Loads a scene file and plays the given shots, stopping early once the level is solved.
+pub fn simulate(
+    path: impl AsRef<Path>,
+    shots: &[Shot],
+) -> Result<SimulationReport, SimulationError> {
+    let mut simulation = Simulation::from_file(path)?;
+    for shot in shots {
+        if simulation.report().solved {
+            break;
+        }
+        simulation.shoot(*shot);
+    }
+    Ok(simulation.report())
+}
+
+/// Same as [`crate::game::strength::apply_level_overrides`] and
+/// [`crate::game::strength::resolve_curve_asset`] in the game,
+/// reading the curve file right away instead of through the asset server.
+fn apply_level_settings(world: &mut World) {
+    let mut settings = level_settings(
+        world.resource::<LevelMetadata>(),
+        world.resource::<GameplaySettings>(),
+    );
+    if let StrengthCurve::Asset(path) = &settings.curve {
+        let file = Path::new(ASSETS_DIR).join(path);
+        match std::fs::read(&file) {
+            Ok(bytes) => match StrengthCurveAsset::from_bytes(&bytes) {
+                Ok(curve) => {
+                    settings.loaded_curve = Some(StrengthCurve::Keyframes(curve.keyframes))
+                }
+                Err(e) => warn!("Could not parse {}: {e}", file.display()),
+            },
+            Err(e) => warn!("Could not read {}: {e}", file.display()),
+        }
+    }
+    world.insert_resource(settings);
+}
+
+fn write_scene(world: &mut World, scene: &DynamicScene) -> Result<(), SimulationError> {
+    scene.write_to_world(world, &mut EntityHashMap::default())?;
+    // Scene files only store the local transform, sprites add the global one in the game.
+    let missing: Vec<Entity> = world
+        .query_filtered::<Entity, (With<Transform>, Without<GlobalTransform>)>()
+        .iter(world)
+        .collect();
+    for entity in missing {
+        world.entity_mut(entity).insert(GlobalTransform::default());
+    }
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use crate::save;
+use crate::states::settings::SettingsWindow;
+use bevy::core::FrameCount;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+pub const BINDINGS_SAVE_KEY: &str = "controls";
+
+/// Everything the player can do with a key or a button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    /// Hold to aim at the pointer, release to shoot.
+    DragAim,
+    /// Shoots the aim set with the keyboard or a gamepad.
+    Shoot,
+    CancelAim,
+    AimLeft,
+    AimRight,
+    StrengthUp,
+    StrengthDown,
+    /// Adds side spin that curves the shot counter-clockwise, the mouse wheel also does.
+    SpinLeft,
+    SpinRight,
+    Pause,
+    Restart,
+    Undo,
+    ToggleInspector,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::DragAim,
+        Action::Shoot,
+        Action::CancelAim,
+        Action::AimLeft,
+        Action::AimRight,
+        Action::StrengthUp,
+        Action::StrengthDown,
+        Action::SpinLeft,
+        Action::SpinRight,
+        Action::Pause,
+        Action::Restart,
+        Action::Undo,
+        Action::ToggleInspector,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::DragAim => "Drag to aim",
+            Action::Shoot => "Shoot",
+            Action::CancelAim => "Cancel aim",
+            Action::AimLeft => "Aim left",
+            Action::AimRight => "Aim right",
+            Action::StrengthUp => "More strength",
+            Action::StrengthDown => "Less strength",
+            Action::SpinLeft => "Spin left",
+            Action::SpinRight => "Spin right",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart level",
+            Action::Undo => "Undo shot",
+            Action::ToggleInspector => "Toggle inspector",
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::*;
+        match self {
+            Action::DragAim => vec![Mouse(MouseButton::Left)],
+            Action::Shoot => vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            // Escape pauses the game when there is nothing to cancel.
+            Action::CancelAim => vec![
+                Mouse(MouseButton::Right),
+                Key(KeyCode::Escape),
+                Gamepad(GamepadButtonType::East),
+            ],
+            Action::AimLeft => vec![Key(KeyCode::ArrowLeft)],
+            Action::AimRight => vec![Key(KeyCode::ArrowRight)],
+            Action::StrengthUp => vec![Key(KeyCode::ArrowUp)],
+            Action::StrengthDown => vec![Key(KeyCode::ArrowDown)],
+            Action::SpinLeft => vec![Key(KeyCode::KeyQ), Gamepad(GamepadButtonType::LeftTrigger)],
+            Action::SpinRight => vec![Key(KeyCode::KeyE), Gamepad(GamepadButtonType::RightTrigger)],
+            Action::Pause => vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+            Action::Restart => vec![Key(KeyCode::KeyR), Gamepad(GamepadButtonType::Select)],
+            Action::Undo => vec![Key(KeyCode::KeyZ), Gamepad(GamepadButtonType::West)],
+            Action::ToggleInspector => vec![Key(KeyCode::F1)],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// The button on any connected gamepad.
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+            Binding::Gamepad(button) => format!("Pad {button:?}"),
+        }
+    }
+}
+
+/// Keys and buttons of every action, persisted between sessions.
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self {
+            actions: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Loads the saved bindings, actions added since the save get their defaults.
+    pub fn load() -> Self {
+        let mut bindings: Self = save::load(BINDINGS_SAVE_KEY);
+        for action in Action::ALL {
+            bindings
+                .actions
+                .entry(action)
+                .or_insert_with(|| action.default_bindings());
+        }
+        bindings
+    }
+}
+
+/// An action waiting for the next key or button to be bound to it.
+#[derive(Resource, Default)]
+pub struct Rebinding {
+    pub capturing: Option<Action>,
+    /// Frame the capture started or ended, input on that frame is ignored.
+    frame: u32,
+}
+
+impl Rebinding {
+    pub fn start(&mut self, action: Action, frame: &FrameCount) {
+        self.capturing = Some(action);
+        self.frame = frame.0;
+    }
+}
+
+/// Actions from all input devices, read through the current [`InputBindings`].
+#[derive(SystemParam)]
+pub struct ActionInput<'w> {
+    bindings: Res<'w, InputBindings>,
+    rebinding: Res<'w, Rebinding>,
+    frame: Res<'w, FrameCount>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+#[derive(Clone, Copy)]
+enum ButtonState {
+    Pressed,
+    JustPressed,
+    JustReleased,
+}
+
+impl ButtonState {
+    fn of<T: Copy + Eq + Hash + Send + Sync + 'static>(
+        self,
+        input: &ButtonInput<T>,
+        button: T,
+    ) -> bool {
+        match self {
+            ButtonState::Pressed => input.pressed(button),
+            ButtonState::JustPressed => input.just_pressed(button),
+            ButtonState::JustReleased => input.just_released(button),
+        }
+    }
+}
+
+impl ActionInput<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.check(action, ButtonState::Pressed)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.check(action, ButtonState::JustPressed)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.check(action, ButtonState::JustReleased)
+    }
+
+    fn check(&self, action: Action, state: ButtonState) -> bool {
+        // Input is not an action while a new binding is captured.
+        if self.rebinding.capturing.is_some() || self.rebinding.frame == self.frame.0 {
+            return false;
+        }
+        self.bindings
+            .get(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => state.of(&self.keys, key),
+                Binding::Mouse(button) => state.of(&self.mouse, button),
+                Binding::Gamepad(button_type) => self.gamepads.iter().any(|gamepad| {
+                    state.of(
+                        &self.gamepad_buttons,
+                        GamepadButton::new(gamepad, button_type),
+                    )
+                }),
+            })
+    }
+}
+
+/// Binds the next released key or button to the action being captured.
+/// Escape cancels the capture.
+pub fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    frame: Res<FrameCount>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut contexts: EguiContexts,
+) {
+    let Some(action) = rebinding.capturing else {
+        return;
+    };
+    // The click that started the capture must not become the binding.
+    if rebinding.frame == frame.0 {
+        return;
+    }
+    let binding = keys
+        .get_just_released()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            // Clicks on the settings window are not bindings.
+            if contexts.ctx_mut().is_pointer_over_area() {
+                return None;
+            }
+            mouse.get_just_released().next().map(|b| Binding::Mouse(*b))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_released()
+                .next()
+                .map(|b| Binding::Gamepad(b.button_type))
+        });
+    let Some(binding) = binding else {
+        return;
+    };
+    rebinding.capturing = None;
+    rebinding.frame = frame.0;
+    if binding == Binding::Key(KeyCode::Escape) {
+        return;
+    }
+    let action_bindings = bindings.actions.entry(action).or_default();
+    if !action_bindings.contains(&binding) {
+        action_bindings.push(binding);
+    }
+}
+
+/// Writes the bindings once the settings window is closed, like the other settings.
+pub fn save_bindings(
+    bindings: Res<InputBindings>,
+    window: Res<SettingsWindow>,
+    mut unsaved: Local<bool>,
+) {
+    if bindings.is_changed() && !bindings.is_added() {
+        *unsaved = true;
+    }
+    if *unsaved && !window.open {
+        save::store(BINDINGS_SAVE_KEY, &*bindings);
+        *unsaved = false;
+    }
+}
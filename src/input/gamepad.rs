@@ -0,0 +1,96 @@
+use crate::game::components::GameplaySettings;
+use crate::input::bindings::{Action, ActionInput};
+use crate::input::{AimEvents, PlayerCoin};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Stick deflection below this is ignored.
+const STICK_DEAD_ZONE: f32 = 0.2;
+const TRIGGER_DEAD_ZONE: f32 = 0.05;
+
+/// Gamepad sticks and triggers.
+#[derive(SystemParam)]
+pub(super) struct GamepadControls<'w> {
+    gamepads: Res<'w, Gamepads>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    analog_buttons: Res<'w, Axis<GamepadButton>>,
+}
+
+impl GamepadControls<'_> {
+    fn left_stick(&self, gamepad: Gamepad) -> Vec2 {
+        let axis = |axis_type| {
+            self.axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        )
+    }
+
+    fn trigger(&self, gamepad: Gamepad) -> f32 {
+        self.analog_buttons
+            .get(GamepadButton::new(
+                gamepad,
+                GamepadButtonType::RightTrigger2,
+            ))
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Default)]
+pub(super) struct GamepadAim {
+    aiming: bool,
+    cancelled: bool,
+}
+
+/// Left stick aims, the right trigger or the stick deflection sets the strength
+/// and the shoot action fires.
+pub(super) fn gamepad_input(
+    controls: GamepadControls,
+    actions: ActionInput,
+    settings: Res<GameplaySettings>,
+    mut state: Local<GamepadAim>,
+    mut aim: AimEvents,
+    mut coin: PlayerCoin,
+) {
+    let active = controls.gamepads.iter().find_map(|gamepad| {
+        let stick = controls.left_stick(gamepad);
+        (stick.length() >= STICK_DEAD_ZONE).then_some((gamepad, stick))
+    });
+    if aim.cancelled() && state.aiming {
+        state.aiming = false;
+        state.cancelled = true;
+    }
+    if state.cancelled {
+        // A cancelled aim stays cancelled until the stick is let go.
+        state.cancelled = active.is_some();
+        return;
+    }
+    let player_pos = coin.resting_position();
+    let (Some((gamepad, stick)), Some(player_pos)) = (active, player_pos) else {
+        if state.aiming {
+            aim.end(false);
+            state.aiming = false;
+        }
+        return;
+    };
+
+    let trigger = controls.trigger(gamepad);
+    let fraction = if trigger > TRIGGER_DEAD_ZONE {
+        trigger
+    } else {
+        (stick.length().min(1.0) - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)
+    };
+    let strength = settings.strength_at(fraction);
+    let direction = settings.aim_mode.shot_direction(stick);
+    if actions.just_pressed(Action::Shoot) {
+        aim.end(true);
+        coin.shoot(direction, strength, aim.spin());
+        state.aiming = false;
+    } else {
+        aim.aim(player_pos, direction, strength);
+        state.aiming = true;
+    }
+}
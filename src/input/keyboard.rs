@@ -0,0 +1,86 @@
+use crate::game::components::GameplaySettings;
+use crate::input::bindings::{Action, ActionInput};
+use crate::input::{AimEvents, PlayerCoin};
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+/// Radians per second while a rotation key is held.
+const ROTATION_SPEED: f32 = PI * 0.5;
+/// Fraction of the strength range per second while a charge key is held.
+const CHARGE_SPEED: f32 = 0.75;
+
+/// Aim kept between frames, so the next shot starts from the last one.
+pub(super) struct KeyboardAim {
+    angle: f32,
+    fraction: f32,
+    active: bool,
+}
+
+impl Default for KeyboardAim {
+    fn default() -> Self {
+        Self {
+            angle: PI * 0.5,
+            fraction: 0.5,
+            active: false,
+        }
+    }
+}
+
+/// Aim left and right rotate the aim, more and less strength change it and shoot fires,
+/// by default the arrow keys and space. Aiming starts with the first of those key presses.
+pub(super) fn keyboard_input(
+    actions: ActionInput,
+    time: Res<Time>,
+    settings: Res<GameplaySettings>,
+    mut state: Local<KeyboardAim>,
+    mut aim: AimEvents,
+    mut coin: PlayerCoin,
+) {
+    if aim.cancelled() {
+        state.active = false;
+    }
+    let aim_actions = [
+        Action::AimLeft,
+        Action::AimRight,
+        Action::StrengthUp,
+        Action::StrengthDown,
+    ];
+    if aim_actions
+        .iter()
+        .any(|action| actions.just_pressed(*action))
+    {
+        state.active = true;
+    }
+    if !state.active {
+        return;
+    }
+    let Some(player_pos) = coin.resting_position() else {
+        aim.end(false);
+        state.active = false;
+        return;
+    };
+
+    let dt = time.delta_seconds();
+    if actions.pressed(Action::AimLeft) {
+        state.angle += ROTATION_SPEED * dt;
+    }
+    if actions.pressed(Action::AimRight) {
+        state.angle -= ROTATION_SPEED * dt;
+    }
+    if actions.pressed(Action::StrengthUp) {
+        state.fraction = (state.fraction + CHARGE_SPEED * dt).min(1.0);
+    }
+    if actions.pressed(Action::StrengthDown) {
+        state.fraction = (state.fraction - CHARGE_SPEED * dt).max(0.0);
+    }
+
+    let direction = Vec2::from_angle(state.angle);
+    let strength = settings.strength_at(state.fraction);
+    if actions.just_pressed(Action::Shoot) {
+        aim.end(true);
+        coin.shoot(direction, strength, aim.spin());
+        state.active = false;
+    } else {
+        aim.aim(player_pos, direction, strength);
+    }
+}
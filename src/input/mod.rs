@@ -1,14 +1,37 @@
-use crate::game::components::{GameplayProgress, GameplaySettings, PlayerControlled};
+pub mod bindings;
+mod gamepad;
+mod keyboard;
+mod touch;
 
+use crate::game::components::{
+    GameplayProgress, GameplaySettings, PlayerControlled, ShotHistory, ShotSnapshot,
+};
+use crate::game::spin::{Spin, MAX_SPIN};
+use crate::game::REST_SPEED;
+use crate::input::bindings::*;
+use crate::states::{GameState, MainState};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy_rapier2d::prelude::*;
 
+/// Spin per line scrolled with the mouse wheel.
+const SPIN_PER_LINE: f32 = 0.1;
+/// Spin per pixel scrolled with a touchpad.
+const SPIN_PER_PIXEL: f32 = 0.005;
+/// Spin per second while a spin action is held.
+const SPIN_SPEED: f32 = 1.0;
+/// Extra turn given to the coin with full spin.
+const SPIN_TORQUE_IMPULSE: f32 = 1.0;
+
 #[derive(Event)]
 pub struct AimingEvent {
     pub player_pos: Vec2,
     pub direction: Vec2,
     pub strength: f32,
+    pub spin: f32,
 }
 
 #[derive(Event)]
@@ -16,6 +39,58 @@ pub struct AimingEndedEvent {
     pub shoot: bool,
 }
 
+/// Whether an aim is in progress, shared by all the aiming inputs.
+#[derive(Resource, Default)]
+pub struct AimState {
+    active: bool,
+    /// The aim in progress was cancelled this frame.
+    cancelled: bool,
+    /// The cancel input is still held, its release must not also pause the game.
+    cancel_held: bool,
+    /// Side spin of the aim in progress, see [`Spin`].
+    spin: f32,
+}
+
+impl AimState {
+    pub fn cancel_held(&self) -> bool {
+        self.cancel_held
+    }
+}
+
+/// Events sent by the aiming input systems.
+#[derive(SystemParam)]
+pub struct AimEvents<'w> {
+    aiming: EventWriter<'w, AimingEvent>,
+    ended: EventWriter<'w, AimingEndedEvent>,
+    state: Res<'w, AimState>,
+}
+
+impl AimEvents<'_> {
+    pub fn aim(&mut self, player_pos: Vec2, direction: Vec2, strength: f32) {
+        self.aiming.send(AimingEvent {
+            player_pos,
+            direction,
+            strength,
+            spin: self.state.spin,
+        });
+    }
+
+    pub fn end(&mut self, shoot: bool) {
+        self.ended.send(AimingEndedEvent { shoot });
+    }
+
+    /// The aim was cancelled with [`Action::CancelAim`] this frame, the input
+    /// systems ignore their aim until it is released.
+    pub fn cancelled(&self) -> bool {
+        self.state.cancelled
+    }
+
+    /// Spin to shoot the coin with.
+    pub fn spin(&self) -> f32 {
+        self.state.spin
+    }
+}
+
 /// We store the world position of the mouse cursor here.
 #[derive(Resource, Default, Reflect, Deref)]
 #[reflect(Resource)]
@@ -31,10 +106,31 @@ impl Plugin for GameInputPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<MouseWorldPosition>()
             .init_resource::<MouseWorldPosition>()
+            .insert_resource(InputBindings::load())
+            .init_resource::<Rebinding>()
+            .init_resource::<AimState>()
             .add_event::<AimingEndedEvent>()
             .add_event::<AimingEvent>()
-            .add_systems(Update, player_input)
-            .add_systems(Update, my_cursor_system);
+            .add_systems(
+                Update,
+                (
+                    (cancel_aim, adjust_spin),
+                    (
+                        player_input,
+                        gamepad::gamepad_input,
+                        keyboard::keyboard_input,
+                        touch::touch_input,
+                        touch::pinch_zoom,
+                    ),
+                    track_aim,
+                )
+                    .chain()
+                    // `GameState` keeps its value outside of the game.
+                    .run_if(in_state(MainState::Game).and_then(in_state(GameState::Playing))),
+            )
+            .add_systems(OnEnter(MainState::Game), touch::reset_zoom)
+            .add_systems(OnExit(MainState::Game), touch::reset_zoom)
+            .add_systems(Update, (my_cursor_system, capture_binding, save_bindings));
     }
 }
 
@@ -63,53 +159,152 @@ fn my_cursor_system(
     }
 }
 
+/// Access to the player coin for the systems shooting it.
+#[derive(SystemParam)]
+pub struct PlayerCoin<'w, 's> {
+    coin: Query<
+        'w,
+        's,
+        (
+            &'static mut ExternalImpulse,
+            &'static mut Spin,
+            &'static Transform,
+            &'static Velocity,
+        ),
+        With<PlayerControlled>,
+    >,
+    progress: ResMut<'w, GameplayProgress>,
+    history: ResMut<'w, ShotHistory>,
+}
+
+impl PlayerCoin<'_, '_> {
+    /// Position of the coin, if there is one and it is at rest.
+    pub fn resting_position(&self) -> Option<Vec2> {
+        let (_, _, transform, velocity) = self.coin.get_single().ok()?;
+        (velocity.linvel.length() < REST_SPEED).then_some(transform.translation.truncate())
+    }
+
+    pub fn shoot(&mut self, direction: Vec2, strength: f32, spin: f32) {
+        let Ok((mut external, mut coin_spin, transform, velocity)) = self.coin.get_single_mut()
+        else {
+            return;
+        };
+        // Another input already shot this frame, rapier only applies the impulse on the next step.
+        if external.impulse != Vec2::ZERO {
+            return;
+        }
+        self.history.shots.push(ShotSnapshot {
+            transform: *transform,
+            velocity: *velocity,
+            progress: self.progress.clone(),
+        });
+        external.impulse = direction * strength;
+        // The turn of the coin only shows the spin, the curve comes from [`Spin`].
+        external.torque_impulse = 0.3 + spin * SPIN_TORQUE_IMPULSE;
+        coin_spin.0 = spin.clamp(-MAX_SPIN, MAX_SPIN);
+        self.progress.moves += 1;
+    }
+}
+
+fn cancel_aim(
+    actions: ActionInput,
+    mut state: ResMut<AimState>,
+    mut ended: EventWriter<AimingEndedEvent>,
+) {
+    state.cancelled = false;
+    if state.active && actions.just_pressed(Action::CancelAim) {
+        state.active = false;
+        state.cancelled = true;
+        state.cancel_held = true;
+        ended.send(AimingEndedEvent { shoot: false });
+    } else if !actions.pressed(Action::CancelAim) && !actions.just_released(Action::CancelAim) {
+        state.cancel_held = false;
+    }
+}
+
+/// Spin changes with the mouse wheel or the spin actions while aiming.
+fn adjust_spin(
+    actions: ActionInput,
+    time: Res<Time>,
+    mut wheel: EventReader<MouseWheel>,
+    mut state: ResMut<AimState>,
+) {
+    let scrolled: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SPIN_PER_LINE,
+            MouseScrollUnit::Pixel => event.y * SPIN_PER_PIXEL,
+        })
+        .sum();
+    if !state.active {
+        return;
+    }
+    let mut spin = state.spin + scrolled;
+    if actions.pressed(Action::SpinLeft) {
+        spin += SPIN_SPEED * time.delta_seconds();
+    }
+    if actions.pressed(Action::SpinRight) {
+        spin -= SPIN_SPEED * time.delta_seconds();
+    }
+    state.spin = spin.clamp(-MAX_SPIN, MAX_SPIN);
+}
+
+fn track_aim(
+    mut state: ResMut<AimState>,
+    mut aiming: EventReader<AimingEvent>,
+    mut ended: EventReader<AimingEndedEvent>,
+) {
+    if ended.read().count() > 0 {
+        state.active = false;
+        state.spin = 0.0;
+    } else if aiming.read().count() > 0 {
+        state.active = true;
+    }
+    aiming.clear();
+}
+
 fn player_input(
-    buttons: Res<ButtonInput<MouseButton>>,
+    actions: ActionInput,
     mouse_pos: Res<MouseWorldPosition>,
     settings: Res<GameplaySettings>,
-    mut aim_event: EventWriter<AimingEvent>,
-    mut aim_event_2: EventWriter<AimingEndedEvent>,
-    mut ext_impulses: Query<(&mut ExternalImpulse, &Transform, &Velocity), With<PlayerControlled>>,
-    mut progress: ResMut<GameplayProgress>,
+    mut aim: AimEvents,
+    mut coin: PlayerCoin,
+    mut cancelled: Local<bool>,
 ) {
-    if ext_impulses.is_empty() {
+    if aim.cancelled() && actions.pressed(Action::DragAim) {
+        *cancelled = true;
+    }
+    if *cancelled {
+        // Releasing the button after a cancel does not shoot.
+        *cancelled = actions.pressed(Action::DragAim);
         return;
     }
-    let released = buttons.just_released(MouseButton::Left);
-    if buttons.pressed(MouseButton::Left) || released {
+    let released = actions.just_released(Action::DragAim);
+    if actions.pressed(Action::DragAim) || released {
         let position = mouse_pos.0;
-        let (mut external, transform, velocity) = ext_impulses.single_mut();
 
-        if velocity.linvel.length() > 0.1 {
+        let Some(player_pos) = coin.resting_position() else {
             if released {
-                aim_event_2.send(AimingEndedEvent { shoot: false });
+                aim.end(false);
             }
             return;
-        }
+        };
 
-        let player_pos = Vec2::new(transform.translation.x, transform.translation.y);
         let distance = position.distance(player_pos);
         let strength = settings.get_shoot_strength(distance);
         if strength.is_none() {
             if released {
-                aim_event_2.send(AimingEndedEvent { shoot: false });
+                aim.end(false);
             }
             return;
         }
         let strength = strength.unwrap();
-        let dir = (position - player_pos).normalize();
+        let dir = settings.aim_mode.shot_direction(position - player_pos);
         if released {
-            eprintln!("{},{},{},{}", position, player_pos, dir, strength);
-            aim_event_2.send(AimingEndedEvent { shoot: true });
-            external.impulse = dir * strength;
-            external.torque_impulse = 0.3;
-            progress.moves += 1;
+            aim.end(true);
+            coin.shoot(dir, strength, aim.spin());
         } else {
-            aim_event.send(AimingEvent {
-                player_pos,
-                strength,
-                direction: dir,
-            });
+            aim.aim(player_pos, dir, strength);
         }
     }
 }
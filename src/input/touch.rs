@@ -0,0 +1,113 @@
+use crate::game::components::GameplaySettings;
+use crate::input::{AimEvents, MainCamera, PlayerCoin};
+use bevy::ecs::system::SystemParam;
+use bevy::input::touch::Touch;
+use bevy::prelude::*;
+use bevy_button_released_plugin::GameButton;
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+
+/// Touches and what is needed to map them to the world.
+#[derive(SystemParam)]
+pub(super) struct TouchScreen<'w, 's> {
+    touches: Res<'w, Touches>,
+    camera: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<MainCamera>>,
+    buttons: Query<'w, 's, &'static Interaction, With<GameButton>>,
+}
+
+impl TouchScreen<'_, '_> {
+    fn world_position(&self, touch: &Touch) -> Option<Vec2> {
+        let (camera, camera_transform) = self.camera.get_single().ok()?;
+        camera
+            .viewport_to_world(camera_transform, touch.position())
+            .map(|ray| ray.origin.truncate())
+    }
+
+    /// Touches on buttons are taps, not aiming.
+    fn on_button(&self) -> bool {
+        self.buttons
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed)
+    }
+}
+
+/// A single finger aims like the mouse and lifting it shoots.
+/// A second finger cancels the aim, it is zooming instead.
+pub(super) fn touch_input(
+    screen: TouchScreen,
+    settings: Res<GameplaySettings>,
+    mut aiming: Local<bool>,
+    mut cancelled: Local<bool>,
+    mut aim: AimEvents,
+    mut coin: PlayerCoin,
+) {
+    let pressed = screen.touches.iter().count();
+    if aim.cancelled() && *aiming {
+        *aiming = false;
+        *cancelled = true;
+    }
+    if *cancelled {
+        // Lifting the finger after a cancel does not shoot.
+        *cancelled = pressed > 0;
+        return;
+    }
+    let released = screen.touches.iter_just_released().next();
+    let touch = match (pressed, released) {
+        (1, None) => screen.touches.iter().next(),
+        (0, Some(released)) if *aiming => Some(released),
+        _ => None,
+    };
+    let target = touch
+        .filter(|_| !screen.on_button())
+        .and_then(|touch| screen.world_position(touch))
+        .zip(coin.resting_position())
+        .and_then(|(position, player_pos)| {
+            let strength = settings.get_shoot_strength(position.distance(player_pos))?;
+            let direction = settings.aim_mode.shot_direction(position - player_pos);
+            Some((player_pos, direction, strength))
+        });
+    let Some((player_pos, direction, strength)) = target else {
+        if *aiming {
+            aim.end(false);
+            *aiming = false;
+        }
+        return;
+    };
+
+    if released.is_some() {
+        aim.end(true);
+        coin.shoot(direction, strength, aim.spin());
+        *aiming = false;
+    } else {
+        aim.aim(player_pos, direction, strength);
+        *aiming = true;
+    }
+}
+
+pub(super) fn pinch_zoom(
+    touches: Res<Touches>,
+    mut projection: Query<&mut OrthographicProjection, With<MainCamera>>,
+) {
+    let mut pressed = touches.iter();
+    let (Some(first), Some(second), None) = (pressed.next(), pressed.next(), pressed.next()) else {
+        return;
+    };
+    let previous = first
+        .previous_position()
+        .distance(second.previous_position());
+    let current = first.position().distance(second.position());
+    if previous <= 0.0 || current <= 0.0 {
+        return;
+    }
+    for mut projection in &mut projection {
+        projection.scale = (projection.scale * previous / current).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// Every level starts, and the other screens are shown, without the pinch zoom.
+pub(super) fn reset_zoom(mut projection: Query<&mut OrthographicProjection, With<MainCamera>>) {
+    for mut projection in &mut projection {
+        projection.scale = 1.0;
+    }
+}
@@ -0,0 +1,12 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod consts;
+pub mod debug;
+pub mod editor;
+pub mod game;
+pub mod headless;
+pub mod input;
+pub mod save;
+pub mod solver;
+pub mod states;
+pub mod utils;
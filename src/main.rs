@@ -1,26 +1,29 @@
-mod consts;
-mod debug;
-mod editor;
-mod game;
-mod input;
-mod states;
-pub mod utils;
-
-use crate::game::GamePlugin;
-use crate::input::GameInputPlugin;
-use crate::states::GameStatesPlugin;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use editor::MapEditorPlugin;
+use coins_bevy::debug;
+use coins_bevy::editor::MapEditorPlugin;
+use coins_bevy::game::GamePlugin;
+use coins_bevy::input::GameInputPlugin;
+use coins_bevy::states::GameStatesPlugin;
 
 fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if !args.is_empty() {
+            std::process::exit(coins_bevy::cli::run(&args));
+        }
+    }
+
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.09, 0.09, 0.13)))
         .insert_resource(Msaa::Off)
         .add_plugins((
             DefaultPlugins,
             debug::DebugPlugin,
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(
+                coins_bevy::consts::PIXELS_PER_METER,
+            ),
             RapierDebugRenderPlugin::default(),
             GameInputPlugin,
             GamePlugin,
@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+/// Bump when the layout of saved data changes in an incompatible way.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile<T> {
+    version: u32,
+    data: T,
+}
+
+#[derive(Debug, Error)]
+enum LoadError {
+    #[error("has version {0}, expected {SAVE_VERSION}")]
+    Version(u32),
+    #[error("is corrupted: {0}")]
+    Corrupted(#[from] ron::error::SpannedError),
+}
+
+/// Reads the data stored under `key`, falling back to the default value
+/// when there is no save yet or when it cannot be used.
+/// A save that cannot be used is kept as a backup.
+pub fn load<T: DeserializeOwned + Default>(key: &str) -> T {
+    let Some(text) = storage::read(key) else {
+        info!("No saved {key} found, starting fresh");
+        return T::default();
+    };
+    match parse(&text) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Saved {key} {e}");
+            storage::backup(key, &text);
+            T::default()
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(text: &str) -> Result<T, LoadError> {
+    let header = ron::from_str::<SaveHeader>(text)?;
+    if header.version != SAVE_VERSION {
+        return Err(LoadError::Version(header.version));
+    }
+    Ok(ron::from_str::<SaveFile<T>>(text)?.data)
+}
+
+/// Writes `data` under `key`, replacing the previous save.
+pub fn store<T: Serialize>(key: &str, data: &T) {
+    let file = SaveFile {
+        version: SAVE_VERSION,
+        data,
+    };
+    match ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()) {
+        Ok(text) => storage::write(key, &text),
+        Err(e) => error!("Could not serialize {key}: {e}"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use bevy::prelude::*;
+    #[cfg(test)]
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    #[cfg(test)]
+    thread_local! {
+        static TEST_DIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+    }
+
+    /// Tests must not touch the real saves.
+    #[cfg(test)]
+    fn save_dir() -> PathBuf {
+        TEST_DIR
+            .with_borrow(Clone::clone)
+            .expect("tests that save must create a TestDir first")
+    }
+
+    #[cfg(not(test))]
+    fn save_dir() -> PathBuf {
+        dirs::data_dir()
+            .map(|dir| dir.join("coins_bevy"))
+            .unwrap_or_else(|| PathBuf::from("saves"))
+    }
+
+    fn path(key: &str) -> PathBuf {
+        save_dir().join(format!("{key}.ron"))
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)).ok()
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let path = path(key);
+        // Write next to the target first so a crash never leaves a half written save.
+        let tmp_path = path.with_extension("ron.tmp");
+        let result = std::fs::create_dir_all(save_dir())
+            .and_then(|_| std::fs::write(&tmp_path, text))
+            .and_then(|_| std::fs::rename(&tmp_path, &path));
+        if let Err(e) = result {
+            error!("Could not write {}: {e}", path.display());
+        }
+    }
+
+    fn backup_path(key: &str) -> PathBuf {
+        path(key).with_extension("ron.bak")
+    }
+
+    pub fn backup(key: &str, text: &str) {
+        let path = backup_path(key);
+        match std::fs::write(&path, text) {
+            Ok(_) => warn!("Previous save kept as {}", path.display()),
+            Err(e) => error!("Could not write {}: {e}", path.display()),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn read_backup(key: &str) -> Option<String> {
+        std::fs::read_to_string(backup_path(key)).ok()
+    }
+
+    /// Saves of the test running on this thread go to a temporary folder,
+    /// removed with everything in it when this is dropped.
+    #[cfg(test)]
+    pub struct TestDir(PathBuf);
+
+    #[cfg(test)]
+    impl TestDir {
+        pub fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("coins_bevy_test_{}_{name}", std::process::id()));
+            TEST_DIR.set(Some(dir.clone()));
+            Self(dir)
+        }
+    }
+
+    #[cfg(test)]
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            TEST_DIR.set(None);
+            if let Err(e) = std::fs::remove_dir_all(&self.0) {
+                error!("Could not remove {}: {e}", self.0.display());
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    use bevy::prelude::*;
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn item_name(key: &str) -> String {
+        format!("coins_bevy.{key}")
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        local_storage()?.get_item(&item_name(key)).ok()?
+    }
+
+    pub fn write(key: &str, text: &str) {
+        let Some(storage) = local_storage() else {
+            error!("Local storage is not available, {key} was not saved");
+            return;
+        };
+        if storage.set_item(&item_name(key), text).is_err() {
+            error!("Could not write {key} to local storage");
+        }
+    }
+
+    pub fn backup(key: &str, text: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(&format!("{}.bak", item_name(key)), text);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    #[serde(default)]
+    struct Data {
+        value: i32,
+    }
+
+    #[test]
+    fn stored_data_is_loaded_back() {
+        let _dir = storage::TestDir::new("stored");
+        store("stored", &Data { value: 7 });
+        assert_eq!(load::<Data>("stored"), Data { value: 7 });
+    }
+
+    #[test]
+    fn other_version_is_not_used() {
+        let text = "(version: 0, data: (value: 7))";
+        assert!(matches!(parse::<Data>(text), Err(LoadError::Version(0))));
+    }
+
+    #[test]
+    fn corrupted_save_is_not_used() {
+        assert!(matches!(
+            parse::<Data>("(version: 1, data: (value: \"seven\"))"),
+            Err(LoadError::Corrupted(_))
+        ));
+        assert!(matches!(
+            parse::<Data>("not a save"),
+            Err(LoadError::Corrupted(_))
+        ));
+    }
+
+    #[test]
+    fn unusable_save_is_backed_up() {
+        let _dir = storage::TestDir::new("corrupted");
+        storage::write("corrupted", "not a save");
+        assert_eq!(load::<Data>("corrupted"), Data::default());
+        assert_eq!(storage::read_backup("corrupted").unwrap(), "not a save");
+    }
+}
@@ -0,0 +1,197 @@
+//! Searches for shot sequences that finish a level, using the headless simulation.
+//!
+//! Shots are taken from a grid of angles, strengths and spins. The search goes one
+//! move deeper at a time, but only the positions closest to the end point are
+//! expanded further (a beam search). It is a heuristic: a solution found is not
+//! always the shortest one, and finding none does not prove the level unsolvable,
+//! unless every position is kept with [`SolverOptions::EXHAUSTIVE`].
+
+use crate::game::components::{EndPoint, GameplaySettings};
+use crate::game::spin::MAX_SPIN;
+use crate::game::COIN_RADIUS;
+use crate::headless::{Shot, Simulation, SimulationError, SimulationReport};
+use bevy::prelude::*;
+use std::path::Path;
+
+#[derive(Clone, Debug)]
+pub struct SolverOptions {
+    /// Directions tried for every shot, evenly spread around the coin.
+    pub angle_steps: usize,
+    /// Strengths tried for every direction, evenly spread over the pull like the player aims.
+    pub strength_steps: usize,
+    /// Side spins tried for every strength, from full spin one way to the other.
+    /// A single step only tries shots without spin.
+    pub spin_steps: usize,
+    pub max_moves: usize,
+    /// Unsolved positions kept after each move, closest to the end point first.
+    /// [`SolverOptions::EXHAUSTIVE`] keeps all of them, which is only practical for a few moves.
+    pub beam_width: usize,
+}
+
+impl SolverOptions {
+    /// Beam width trying every sequence of shots from the grid.
+    pub const EXHAUSTIVE: usize = usize::MAX;
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            angle_steps: 36,
+            strength_steps: 6,
+            spin_steps: 3,
+            max_moves: 3,
+            beam_width: 6,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Solution {
+    pub shots: Vec<Shot>,
+    pub report: SimulationReport,
+}
+
+struct Candidate {
+    shots: Vec<Shot>,
+    report: SimulationReport,
+    distance: f32,
+}
+
+pub fn solve_file(
+    path: impl AsRef<Path>,
+    options: &SolverOptions,
+) -> Result<Option<Solution>, SimulationError> {
+    solve(&std::fs::read_to_string(path)?, options)
+}
+
+/// Returns the first solution found with the fewest moves among the searched positions,
+/// and among those the fewest collisions, or `None` if no searched sequence of at most
+/// `options.max_moves` shots finishes the level.
+pub fn solve(scene: &str, options: &SolverOptions) -> Result<Option<Solution>, SimulationError> {
+    let start = Simulation::from_scene_str(scene)?;
+    let Some(end_point) = end_point_position(&start) else {
+        return Ok(None);
+    };
+    let shots = shot_grid(start.world().resource::<GameplaySettings>(), options);
+
+    let mut frontier = vec![Vec::new()];
+    for _ in 0..options.max_moves {
+        let mut candidates = Vec::new();
+        for prefix in &frontier {
+            // The app can not be cloned, so every position is played once from the start
+            // and each shot is tried from a snapshot of it.
+            let mut simulation = Simulation::from_scene_str(scene)?;
+            for shot in prefix {
+                simulation.shoot(*shot);
+            }
+            let snapshot = simulation.snapshot();
+            for shot in &shots {
+                simulation.shoot(*shot);
+                let report = simulation.report();
+                simulation.restore(&snapshot);
+                let mut sequence: Vec<Shot> = prefix.clone();
+                sequence.push(*shot);
+                candidates.push(Candidate {
+                    distance: report.position.distance(end_point),
+                    shots: sequence,
+                    report,
+                });
+            }
+        }
+
+        if let Some(solution) = verified_solution(scene, &candidates)? {
+            return Ok(Some(solution));
+        }
+
+        frontier = prune(candidates, options.beam_width)
+            .into_iter()
+            .map(|c| c.shots)
+            .collect();
+    }
+    Ok(None)
+}
+
+/// Plays the solving candidates again from the start, fewest collisions first, since a
+/// restored simulation can end slightly differently, and returns the first that still wins.
+fn verified_solution(
+    scene: &str,
+    candidates: &[Candidate],
+) -> Result<Option<Solution>, SimulationError> {
+    let mut solved: Vec<&Candidate> = candidates.iter().filter(|c| c.report.solved).collect();
+    solved.sort_by_key(|c| c.report.touches);
+    for candidate in solved {
+        let mut simulation = Simulation::from_scene_str(scene)?;
+        for shot in &candidate.shots {
+            simulation.shoot(*shot);
+        }
+        let report = simulation.report();
+        if report.solved {
+            return Ok(Some(Solution {
+                shots: candidate.shots.clone(),
+                report,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// Keeps the `beam_width` unsolved positions closest to the end point.
+fn prune(mut candidates: Vec<Candidate>, beam_width: usize) -> Vec<Candidate> {
+    if beam_width == SolverOptions::EXHAUSTIVE {
+        return candidates;
+    }
+    candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        if kept.len() >= beam_width {
+            break;
+        }
+        // Shots ending in about the same place lead to the same follow ups.
+        let duplicate = kept
+            .iter()
+            .any(|k| k.report.position.distance(candidate.report.position) < COIN_RADIUS);
+        if !duplicate {
+            kept.push(candidate);
+        }
+    }
+    kept
+}
+
+fn shot_grid(settings: &GameplaySettings, options: &SolverOptions) -> Vec<Shot> {
+    let strength_steps = options.strength_steps.max(1);
+    let spin_steps = options.spin_steps.max(1);
+    let mut shots = Vec::with_capacity(options.angle_steps * strength_steps * spin_steps);
+    for a in 0..options.angle_steps {
+        let angle = a as f32 * 360.0 / options.angle_steps as f32;
+        for s in 0..strength_steps {
+            for p in 0..spin_steps {
+                shots.push(Shot {
+                    angle,
+                    // Through the strength curve, so every shot is one the player can make.
+                    strength: settings.strength_at(fraction(s, strength_steps, 1.0)),
+                    spin: (fraction(p, spin_steps, 0.5) * 2.0 - 1.0) * MAX_SPIN,
+                    at: None,
+                });
+            }
+        }
+    }
+    shots
+}
+
+/// Step `i` of `steps` evenly spread from 0 to 1, `single` when there is only one step.
+fn fraction(i: usize, steps: usize, single: f32) -> f32 {
+    if steps == 1 {
+        single
+    } else {
+        i as f32 / (steps - 1) as f32
+    }
+}
+
+fn end_point_position(simulation: &Simulation) -> Option<Vec2> {
+    simulation
+        .world()
+        .iter_entities()
+        .find(|e| e.contains::<EndPoint>())
+        .and_then(|e| e.get::<Transform>())
+        .map(|transform| transform.translation.truncate())
+}
@@ -0,0 +1,171 @@
+use crate::consts;
+use crate::game::components::LevelMetadata;
+use crate::game::levels::{Campaign, CurrentLevel};
+use crate::game::records::LevelRecords;
+use crate::states::{button_text_style, spawn_button, MainState, MenuButtonActivated};
+use crate::utils::despawn_recursive_by_component;
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub enum LevelSelectButton {
+    Play(usize),
+    Locked,
+    Back,
+}
+
+#[derive(Component)]
+pub struct LevelSelectRoot;
+
+pub struct LevelSelectPlugin;
+
+impl Plugin for LevelSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MainState::LevelSelect), setup_level_select)
+            .add_systems(
+                Update,
+                (button_system).run_if(in_state(MainState::LevelSelect)),
+            )
+            .add_systems(
+                OnExit(MainState::LevelSelect),
+                despawn_recursive_by_component::<LevelSelectRoot>,
+            );
+    }
+}
+
+fn button_system(
+    mut reader: EventReader<MenuButtonActivated>,
+    interaction_query: Query<&LevelSelectButton>,
+    mut next_state: ResMut<NextState<MainState>>,
+    mut current_level: ResMut<CurrentLevel>,
+) {
+    for event in reader.read() {
+        if let Ok(button_type) = interaction_query.get(**event) {
+            match *button_type {
+                LevelSelectButton::Play(index) => {
+                    current_level.index = index;
+                    next_state.set(MainState::Game);
+                }
+                LevelSelectButton::Locked => {}
+                LevelSelectButton::Back => next_state.set(MainState::Menu),
+            }
+        }
+    }
+}
+
+fn setup_level_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    campaign: Campaign,
+    records: Res<LevelRecords>,
+) {
+    let text_color = Color::hex("ECE3CE").unwrap();
+    let info_style = TextStyle {
+        font: asset_server.load(consts::BASE_FONT),
+        font_size: 16.0,
+        color: consts::MY_ACCENT_COLOR,
+    };
+    let btn_text_style = button_text_style(&asset_server);
+    commands
+        .spawn(NodeBundle {
+            background_color: BackgroundColor::from(Color::hex("3A4D39").unwrap()),
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Name::new("level-select-root"))
+        .insert(LevelSelectRoot)
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Select Level",
+                    TextStyle {
+                        font: asset_server.load(consts::BASE_FONT),
+                        font_size: 55.0,
+                        color: text_color,
+                    },
+                )
+                .with_text_justify(JustifyText::Center)
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Percent(5.0)),
+                    ..default()
+                }),
+            );
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(90.),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (index, level) in campaign.levels().iter().enumerate() {
+                        let unlocked = records.is_unlocked(index, &level.id);
+                        let record = records.get(&level.id);
+                        let info = match record {
+                            _ if !unlocked => "Locked".to_string(),
+                            Some(record) if record.completed => format!(
+                                "{}/{} stars, best: {} moves, {} collisions",
+                                record.best_stars,
+                                LevelMetadata::MAX_STARS,
+                                record.best_moves.unwrap_or_default(),
+                                record.fewest_touches.unwrap_or_default()
+                            ),
+                            _ => "Not completed".to_string(),
+                        };
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(10.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                let text = format!("{}. {}", index + 1, level.name);
+                                if unlocked {
+                                    spawn_button(
+                                        parent,
+                                        &text,
+                                        LevelSelectButton::Play(index),
+                                        UiRect::bottom(Val::Px(5.0)),
+                                        &btn_text_style,
+                                    );
+                                } else {
+                                    spawn_button(
+                                        parent,
+                                        &text,
+                                        LevelSelectButton::Locked,
+                                        UiRect::bottom(Val::Px(5.0)),
+                                        &btn_text_style,
+                                    )
+                                    .insert(BackgroundColor::from(Color::hex("2C3A2B").unwrap()));
+                                }
+                                parent.spawn(TextBundle::from_section(info, info_style.clone()));
+                            });
+                    }
+                });
+
+            spawn_button(
+                parent,
+                "Back",
+                LevelSelectButton::Back,
+                UiRect {
+                    top: Val::Auto,
+                    bottom: Val::Px(15.0),
+                    ..default()
+                },
+                &btn_text_style,
+            );
+        });
+}
@@ -1,12 +1,12 @@
 use crate::consts;
-use crate::states::MainState;
+use crate::game::levels::Campaign;
+use crate::states::{button_text_style, spawn_button, MainState, MenuButtonActivated};
 use bevy::prelude::*;
 
-use bevy_button_released_plugin::{ButtonReleasedEvent, GameButton};
-
 #[derive(Component)]
 pub enum MainMenuButton {
     StartGame,
+    SelectLevel,
     RunEditor,
     Exit,
 }
@@ -19,21 +19,28 @@ pub struct MenuPlugin;
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(MainState::Menu), setup_menu)
-            .add_systems(Update, (button_system).run_if(in_state(MainState::Menu)))
+            .add_systems(
+                Update,
+                (show_campaign_loading, button_system).run_if(in_state(MainState::Menu)),
+            )
             .add_systems(OnExit(MainState::Menu), cleanup_menu);
     }
 }
 
 fn button_system(
-    mut reader: EventReader<ButtonReleasedEvent>,
+    mut reader: EventReader<MenuButtonActivated>,
     interaction_query: Query<&MainMenuButton>,
     mut next_state: ResMut<NextState<MainState>>,
+    campaign: Campaign,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     for event in reader.read() {
         if let Ok(button_type) = interaction_query.get(**event) {
             match *button_type {
+                MainMenuButton::StartGame | MainMenuButton::SelectLevel
+                    if !campaign.is_loaded() => {}
                 MainMenuButton::StartGame => next_state.set(MainState::Game),
+                MainMenuButton::SelectLevel => next_state.set(MainState::LevelSelect),
                 MainMenuButton::RunEditor => next_state.set(MainState::Editor),
                 MainMenuButton::Exit => {
                     #[cfg(target_arch = "wasm32")]
@@ -50,6 +57,27 @@ fn button_system(
     }
 }
 
+/// Greys out the buttons that need the level manifest until it is loaded.
+fn show_campaign_loading(
+    campaign: Campaign,
+    mut buttons: Query<(&MainMenuButton, &mut BackgroundColor)>,
+) {
+    let color = if campaign.is_loaded() {
+        Color::hex("4F6F52").unwrap()
+    } else {
+        Color::hex("2C3A2B").unwrap()
+    };
+    for (button_type, mut background) in &mut buttons {
+        if matches!(
+            button_type,
+            MainMenuButton::StartGame | MainMenuButton::SelectLevel
+        ) && background.0 != color
+        {
+            background.0 = color;
+        }
+    }
+}
+
 fn cleanup_menu(mut commands: Commands, query: Query<Entity, With<MenuRoot>>) {
     let menu_root = query.single();
     commands.entity(menu_root).despawn_recursive();
@@ -93,11 +121,7 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                 }),
             );
 
-            let btn_text_style = TextStyle {
-                font: asset_server.load(consts::BASE_FONT),
-                font_size: 25.0,
-                color: Color::hex("ECE3CE").unwrap(),
-            };
+            let btn_text_style = button_text_style(&asset_server);
 
             for (text, label, margin) in [
                 (
@@ -109,6 +133,14 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                         ..default()
                     },
                 ),
+                (
+                    "Select Level",
+                    MainMenuButton::SelectLevel,
+                    UiRect {
+                        bottom: Val::Px(15.0),
+                        ..default()
+                    },
+                ),
                 (
                     "Run Editor",
                     MainMenuButton::RunEditor,
@@ -127,28 +159,7 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                     },
                 ),
             ] {
-                parent
-                    .spawn((
-                        ButtonBundle {
-                            style: Style {
-                                width: Val::Px(200.0),
-                                height: Val::Px(50.0),
-                                margin,
-                                align_items: AlignItems::Center,
-                                justify_content: JustifyContent::Center,
-                                ..default()
-                            },
-
-                            background_color: BackgroundColor::from(Color::hex("4F6F52").unwrap()),
-                            ..default()
-                        },
-                        Name::new(format!("button:{}", text)),
-                        label,
-                        GameButton::default(),
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle::from_section(text, btn_text_style.clone()));
-                    });
+                spawn_button(parent, text, label, margin, &btn_text_style);
             }
         });
 }
@@ -1,22 +1,94 @@
+mod level_select;
 mod menu;
+mod navigation;
+mod pause;
+mod results;
+pub mod settings;
 
+use crate::consts;
+use crate::states::level_select::LevelSelectPlugin;
 use crate::states::menu::MenuPlugin;
+use crate::states::navigation::NavigationPlugin;
+use crate::states::pause::PausePlugin;
+use crate::states::results::ResultsPlugin;
+use crate::states::settings::SettingsPlugin;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy_button_released_plugin::*;
 
+pub use navigation::MenuButtonActivated;
+
 #[derive(Clone, Debug, Default, Hash, Eq, States, PartialEq)]
 pub enum MainState {
     #[default]
     Menu,
+    LevelSelect,
     Editor,
     Game,
 }
 
+/// Phase of the level being played, only meaningful while in [`MainState::Game`].
+#[derive(Clone, Debug, Default, Hash, Eq, States, PartialEq)]
+pub enum GameState {
+    #[default]
+    Playing,
+    Paused,
+    Completed,
+}
+
 pub struct GameStatesPlugin;
 
 impl Plugin for GameStatesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((MenuPlugin, ButtonsReleasedPlugin))
-            .init_state::<MainState>();
+        app.add_plugins((
+            MenuPlugin,
+            LevelSelectPlugin,
+            PausePlugin,
+            ResultsPlugin,
+            SettingsPlugin,
+            ButtonsReleasedPlugin,
+            NavigationPlugin,
+        ))
+        .init_state::<MainState>()
+        .init_state::<GameState>();
+    }
+}
+
+pub fn spawn_button<'a>(
+    parent: &'a mut ChildBuilder,
+    text: &str,
+    button: impl Component,
+    margin: UiRect,
+    text_style: &TextStyle,
+) -> EntityCommands<'a> {
+    let mut entity_commands = parent.spawn((
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(200.0),
+                height: Val::Px(50.0),
+                margin,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+
+            background_color: BackgroundColor::from(Color::hex("4F6F52").unwrap()),
+            ..default()
+        },
+        Name::new(format!("button:{}", text)),
+        button,
+        GameButton::default(),
+    ));
+    entity_commands.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(text, text_style.clone()));
+    });
+    entity_commands
+}
+
+pub fn button_text_style(asset_server: &AssetServer) -> TextStyle {
+    TextStyle {
+        font: asset_server.load(consts::BASE_FONT),
+        font_size: 25.0,
+        color: Color::hex("ECE3CE").unwrap(),
     }
 }
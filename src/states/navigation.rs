@@ -0,0 +1,178 @@
+use crate::states::{GameState, MainState};
+use bevy::prelude::*;
+use bevy_button_released_plugin::{ButtonReleasedEvent, GameButton};
+
+/// A menu button was clicked, tapped or activated with a gamepad.
+/// Button handlers read this instead of [`ButtonReleasedEvent`].
+#[derive(Event, Deref, Clone, Copy, Debug)]
+pub struct MenuButtonActivated(pub Entity);
+
+/// The button selected with the d-pad, activated with the south face button.
+#[derive(Resource, Default)]
+pub struct FocusedButton(Option<Entity>);
+
+/// The last button activated by a tap, see [`tap_buttons`].
+#[derive(Resource, Default)]
+struct LastTap(Option<(Entity, f32)>);
+
+const FOCUS_OUTLINE_COLOR: &str = "ECE3CE";
+/// A touch moving further than this is a drag, not a tap.
+const TAP_MAX_DISTANCE: f32 = 20.0;
+/// Button releases this soon after a tap on the same button are not sent again.
+const TAP_DEBOUNCE_SECONDS: f32 = 0.5;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuButtonActivated>()
+            .init_resource::<FocusedButton>()
+            .init_resource::<LastTap>()
+            .add_systems(Update, (tap_buttons, forward_released_buttons).chain())
+            .add_systems(
+                Update,
+                (navigate_buttons, activate_focused_button, show_focus)
+                    .chain()
+                    // The face button shoots the coin while playing.
+                    .run_if(
+                        not(in_state(MainState::Game)).or_else(not(in_state(GameState::Playing))),
+                    ),
+            );
+    }
+}
+
+fn forward_released_buttons(
+    mut reader: EventReader<ButtonReleasedEvent>,
+    mut writer: EventWriter<MenuButtonActivated>,
+    last_tap: Res<LastTap>,
+    time: Res<Time>,
+) {
+    for event in reader.read() {
+        let tapped = last_tap.0.is_some_and(|(entity, at)| {
+            entity == **event && time.elapsed_seconds() - at < TAP_DEBOUNCE_SECONDS
+        });
+        if !tapped {
+            writer.send(MenuButtonActivated(**event));
+        }
+    }
+}
+
+/// Bevy UI does not leave a button hovered when a finger is lifted,
+/// so taps never produce a [`ButtonReleasedEvent`] and are detected here.
+fn tap_buttons(
+    touches: Res<Touches>,
+    buttons: Query<(Entity, &Node, &GlobalTransform, &ViewVisibility), With<GameButton>>,
+    mut last_tap: ResMut<LastTap>,
+    time: Res<Time>,
+    mut writer: EventWriter<MenuButtonActivated>,
+) {
+    for touch in touches.iter_just_released() {
+        if touch.distance().length() > TAP_MAX_DISTANCE {
+            continue;
+        }
+        let tapped = buttons.iter().find(|(_, node, transform, visibility)| {
+            let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+            visibility.get() && rect.contains(touch.position())
+        });
+        if let Some((entity, ..)) = tapped {
+            last_tap.0 = Some((entity, time.elapsed_seconds()));
+            writer.send(MenuButtonActivated(entity));
+        }
+    }
+}
+
+fn navigate_buttons(
+    gamepads: Res<Gamepads>,
+    input: Res<ButtonInput<GamepadButton>>,
+    mut focused: ResMut<FocusedButton>,
+    buttons: Query<(Entity, &GlobalTransform, &ViewVisibility), With<GameButton>>,
+) {
+    let mut direction = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        for (button_type, step) in [
+            (GamepadButtonType::DPadUp, Vec2::NEG_Y),
+            (GamepadButtonType::DPadDown, Vec2::Y),
+            (GamepadButtonType::DPadLeft, Vec2::NEG_X),
+            (GamepadButtonType::DPadRight, Vec2::X),
+        ] {
+            if input.just_pressed(GamepadButton::new(gamepad, button_type)) {
+                direction += step;
+            }
+        }
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    // UI positions grow downwards, so "up" is negative y.
+    let visible: Vec<(Entity, Vec2)> = buttons
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(e, transform, _)| (e, transform.translation().truncate()))
+        .collect();
+    let current = focused
+        .0
+        .and_then(|focused| visible.iter().find(|(e, _)| *e == focused));
+    let Some(&(current, from)) = current else {
+        // Nothing focused yet, start from the top left button.
+        focused.0 = visible
+            .iter()
+            .min_by(|(_, a), (_, b)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap())
+            .map(|(e, _)| *e);
+        return;
+    };
+    let direction = direction.normalize();
+    // The closest button roughly in the pressed direction.
+    focused.0 = visible
+        .iter()
+        .filter(|(e, _)| *e != current)
+        .filter_map(|(e, position)| {
+            let offset = *position - from;
+            let along = offset.dot(direction);
+            (along > 0.0 && along >= offset.length() * 0.5).then_some((*e, offset.length()))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(e, _)| e)
+        .or(Some(current));
+}
+
+fn activate_focused_button(
+    gamepads: Res<Gamepads>,
+    input: Res<ButtonInput<GamepadButton>>,
+    focused: Res<FocusedButton>,
+    buttons: Query<&ViewVisibility, With<GameButton>>,
+    mut writer: EventWriter<MenuButtonActivated>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    if !buttons.get(entity).is_ok_and(|visibility| visibility.get()) {
+        return;
+    }
+    let pressed = gamepads
+        .iter()
+        .any(|gamepad| input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)));
+    if pressed {
+        writer.send(MenuButtonActivated(entity));
+    }
+}
+
+fn show_focus(
+    mut commands: Commands,
+    focused: Res<FocusedButton>,
+    outlined: Query<Entity, (With<Outline>, With<GameButton>)>,
+) {
+    if !focused.is_changed() {
+        return;
+    }
+    for entity in &outlined {
+        commands.entity(entity).remove::<Outline>();
+    }
+    if let Some(mut entity) = focused.0.and_then(|e| commands.get_entity(e)) {
+        entity.insert(Outline::new(
+            Val::Px(3.0),
+            Val::Px(2.0),
+            Color::hex(FOCUS_OUTLINE_COLOR).unwrap(),
+        ));
+    }
+}
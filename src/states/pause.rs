@@ -0,0 +1,166 @@
+use crate::consts;
+use crate::game::RestartLevel;
+use crate::input::bindings::{Action, ActionInput};
+use crate::input::AimState;
+use crate::states::settings::SettingsWindow;
+use crate::states::{button_text_style, spawn_button, GameState, MainState, MenuButtonActivated};
+use crate::utils::despawn_recursive_by_component;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::RapierConfiguration;
+
+#[derive(Component)]
+pub enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+#[derive(Component)]
+pub struct PauseRoot;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Paused), (setup_pause, freeze_physics))
+            .add_systems(
+                OnExit(GameState::Paused),
+                (
+                    despawn_recursive_by_component::<PauseRoot>,
+                    resume_physics,
+                    close_settings,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_pause,
+                    (button_system, hide_behind_settings).run_if(in_state(GameState::Paused)),
+                )
+                    .run_if(in_state(MainState::Game)),
+            );
+    }
+}
+
+fn freeze_physics(mut config: ResMut<RapierConfiguration>) {
+    config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut config: ResMut<RapierConfiguration>) {
+    config.physics_pipeline_active = true;
+}
+
+fn close_settings(mut settings_window: ResMut<SettingsWindow>) {
+    settings_window.open = false;
+}
+
+fn toggle_pause(
+    actions: ActionInput,
+    aim: Res<AimState>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_main_state: ResMut<NextState<MainState>>,
+    mut settings_window: ResMut<SettingsWindow>,
+) {
+    // The same key may have cancelled an aim instead.
+    if !actions.just_released(Action::Pause) || aim.cancel_held() {
+        return;
+    }
+    match state.get() {
+        GameState::Playing => next_state.set(GameState::Paused),
+        GameState::Paused if settings_window.open => settings_window.open = false,
+        GameState::Paused => next_state.set(GameState::Playing),
+        GameState::Completed => next_main_state.set(MainState::Menu),
+    }
+}
+
+fn button_system(
+    mut reader: EventReader<MenuButtonActivated>,
+    interaction_query: Query<&PauseButton>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_main_state: ResMut<NextState<MainState>>,
+    mut restart: EventWriter<RestartLevel>,
+    mut settings_window: ResMut<SettingsWindow>,
+) {
+    for event in reader.read() {
+        if let Ok(button_type) = interaction_query.get(**event) {
+            match *button_type {
+                PauseButton::Resume => next_state.set(GameState::Playing),
+                PauseButton::Restart => {
+                    restart.send(RestartLevel);
+                }
+                PauseButton::Settings => settings_window.open = true,
+                PauseButton::QuitToMenu => next_main_state.set(MainState::Menu),
+            }
+        }
+    }
+}
+
+/// Hidden nodes are not interactable, so clicks on the settings window
+/// do not reach the buttons below it.
+fn hide_behind_settings(
+    settings_window: Res<SettingsWindow>,
+    mut query: Query<&mut Visibility, With<PauseRoot>>,
+) {
+    for mut visibility in &mut query {
+        visibility.set_if_neq(if settings_window.open {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+}
+
+fn setup_pause(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            background_color: BackgroundColor::from(Color::hex("3A4D39DD").unwrap()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(Name::new("pause-root"))
+        .insert(PauseRoot)
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Paused",
+                    TextStyle {
+                        font: asset_server.load(consts::BASE_FONT),
+                        font_size: 55.0,
+                        color: Color::hex("ECE3CE").unwrap(),
+                    },
+                )
+                .with_text_justify(JustifyText::Center)
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(25.0)),
+                    ..default()
+                }),
+            );
+
+            let btn_text_style = button_text_style(&asset_server);
+            for (text, label) in [
+                ("Resume", PauseButton::Resume),
+                ("Restart Level", PauseButton::Restart),
+                ("Settings", PauseButton::Settings),
+                ("Quit to Menu", PauseButton::QuitToMenu),
+            ] {
+                spawn_button(
+                    parent,
+                    text,
+                    label,
+                    UiRect::bottom(Val::Px(15.0)),
+                    &btn_text_style,
+                );
+            }
+        });
+}
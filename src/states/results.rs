@@ -0,0 +1,165 @@
+use crate::consts;
+use crate::game::components::LevelMetadata;
+use crate::game::levels::{Campaign, CurrentLevel};
+use crate::game::{LevelCompleted, RestartLevel};
+use crate::states::{button_text_style, spawn_button, GameState, MainState, MenuButtonActivated};
+use crate::utils::despawn_recursive_by_component;
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub enum ResultsButton {
+    Retry,
+    NextLevel,
+    BackToMenu,
+}
+
+#[derive(Component)]
+pub struct ResultsRoot;
+
+pub struct ResultsPlugin;
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (show_results, button_system).run_if(in_state(MainState::Game)),
+        )
+        .add_systems(
+            OnExit(GameState::Completed),
+            despawn_recursive_by_component::<ResultsRoot>,
+        )
+        .add_systems(OnExit(MainState::Game), back_to_playing);
+    }
+}
+
+fn back_to_playing(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn button_system(
+    mut reader: EventReader<MenuButtonActivated>,
+    interaction_query: Query<&ResultsButton>,
+    mut next_state: ResMut<NextState<MainState>>,
+    mut restart: EventWriter<RestartLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+    campaign: Campaign,
+) {
+    for event in reader.read() {
+        if let Ok(button_type) = interaction_query.get(**event) {
+            match *button_type {
+                ResultsButton::Retry => {
+                    restart.send(RestartLevel);
+                }
+                ResultsButton::NextLevel => {
+                    if campaign.has_next(current_level.index) {
+                        current_level.index += 1;
+                        restart.send(RestartLevel);
+                    } else {
+                        next_state.set(MainState::Menu);
+                    }
+                }
+                ResultsButton::BackToMenu => next_state.set(MainState::Menu),
+            }
+        }
+    }
+}
+
+fn show_results(
+    mut commands: Commands,
+    mut reader: EventReader<LevelCompleted>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
+    campaign: Campaign,
+    metadata: Res<LevelMetadata>,
+) {
+    let Some(LevelCompleted { progress, stars }) = reader.read().last() else {
+        return;
+    };
+    let mut summary = format!(
+        "Stars: {} / {}\nMoves: {}",
+        stars,
+        LevelMetadata::MAX_STARS,
+        progress.moves
+    );
+    if let Some(par) = metadata.par_moves {
+        summary.push_str(&format!(" (par {par})"));
+    }
+    summary.push_str(&format!("\nCollisions: {}", progress.touches));
+    if let Some(max_collisions) = metadata.max_collisions {
+        summary.push_str(&format!(" (max {max_collisions})"));
+    }
+    if progress.undos > 0 {
+        summary.push_str(&format!("\nUndos: {}", progress.undos));
+    }
+    let text_color = Color::hex("ECE3CE").unwrap();
+    commands
+        .spawn(NodeBundle {
+            background_color: BackgroundColor::from(Color::hex("3A4D39DD").unwrap()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(Name::new("results-root"))
+        .insert(ResultsRoot)
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    campaign
+                        .get(current_level.index)
+                        .map_or("Level Complete".to_string(), |level| {
+                            format!("{} Complete", level.name)
+                        }),
+                    TextStyle {
+                        font: asset_server.load(consts::BASE_FONT),
+                        font_size: 55.0,
+                        color: text_color,
+                    },
+                )
+                .with_text_justify(JustifyText::Center)
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(25.0)),
+                    ..default()
+                }),
+            );
+            parent.spawn(
+                TextBundle::from_section(
+                    summary,
+                    TextStyle {
+                        font: asset_server.load(consts::BASE_FONT),
+                        font_size: 30.0,
+                        color: consts::MY_ACCENT_COLOR,
+                    },
+                )
+                .with_text_justify(JustifyText::Center)
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(25.0)),
+                    ..default()
+                }),
+            );
+
+            let btn_text_style = button_text_style(&asset_server);
+            let next_level = campaign
+                .has_next(current_level.index)
+                .then_some(("Next Level", ResultsButton::NextLevel));
+            for (text, label) in next_level.into_iter().chain([
+                ("Retry", ResultsButton::Retry),
+                ("Back to Menu", ResultsButton::BackToMenu),
+            ]) {
+                spawn_button(
+                    parent,
+                    text,
+                    label,
+                    UiRect::bottom(Val::Px(15.0)),
+                    &btn_text_style,
+                );
+            }
+        });
+}
@@ -0,0 +1,175 @@
+use crate::consts::*;
+use crate::game::components::{AimMode, GameplaySettings};
+use crate::input::bindings::{Action, InputBindings, Rebinding};
+use crate::save;
+use bevy::audio::Volume;
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+pub const SETTINGS_SAVE_KEY: &str = "settings";
+
+/// Player preferences, persisted between sessions.
+#[derive(Resource, Reflect, Clone, PartialEq, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct UserSettings {
+    pub music_volume: f32,
+    /// Draw the predicted path while aiming, on levels that allow it.
+    pub show_trajectory: bool,
+    /// Copied to [`GameplaySettings::aim_mode`].
+    pub aim_mode: AimMode,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            music_volume: 1.0,
+            show_trajectory: true,
+            aim_mode: AimMode::default(),
+        }
+    }
+}
+
+impl UserSettings {
+    pub fn music_playback(&self) -> PlaybackSettings {
+        PlaybackSettings::ONCE.with_volume(Volume::new(self.music_volume))
+    }
+}
+
+/// Whether the settings window is shown.
+#[derive(Resource, Default)]
+pub struct SettingsWindow {
+    pub open: bool,
+}
+
+/// Marks the audio entity playing the background music.
+#[derive(Component)]
+pub struct Music;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<UserSettings>()
+            .insert_resource(save::load::<UserSettings>(SETTINGS_SAVE_KEY))
+            .init_resource::<SettingsWindow>()
+            .add_systems(
+                Update,
+                (
+                    settings_ui.run_if(|window: Res<SettingsWindow>| window.open),
+                    apply_music_volume,
+                    apply_aim_mode,
+                    save_settings,
+                ),
+            );
+    }
+}
+
+fn settings_ui(
+    mut contexts: EguiContexts,
+    mut window: ResMut<SettingsWindow>,
+    mut settings: ResMut<UserSettings>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    frame: Res<FrameCount>,
+) {
+    let mut edited = settings.clone();
+    let mut edited_bindings = bindings.clone();
+    egui::Window::new(
+        egui::RichText::new("Settings")
+            .strong()
+            .color(MY_ACCENT_COLOR32),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+    .show(contexts.ctx_mut(), |ui| {
+        ui.add(egui::Slider::new(&mut edited.music_volume, 0.0..=1.0).text("Music volume"));
+        ui.checkbox(&mut edited.show_trajectory, "Show trajectory preview");
+        egui::ComboBox::from_label("Aiming")
+            .selected_text(edited.aim_mode.label())
+            .show_ui(ui, |ui| {
+                for mode in AimMode::ALL {
+                    ui.selectable_value(&mut edited.aim_mode, mode, mode.label());
+                }
+            });
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new("Controls").strong());
+        egui::Grid::new("controls")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(action.label());
+                    ui.horizontal(|ui| {
+                        let action_bindings = edited_bindings.actions.entry(action).or_default();
+                        let mut removed = None;
+                        for (i, binding) in action_bindings.iter().enumerate() {
+                            if ui
+                                .button(binding.label())
+                                .on_hover_text("Click to remove")
+                                .clicked()
+                            {
+                                removed = Some(i);
+                            }
+                        }
+                        if let Some(i) = removed {
+                            action_bindings.remove(i);
+                        }
+                        if rebinding.capturing == Some(action) {
+                            ui.label("Press a key or button, Escape cancels");
+                        } else if ui.button("+").clicked() {
+                            rebinding.start(action, &frame);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        if ui.button("Reset controls").clicked() {
+            edited_bindings = InputBindings::default();
+        }
+        ui.add_space(10.0);
+        if ui.button("Close").clicked() {
+            window.open = false;
+            rebinding.capturing = None;
+        }
+    });
+    if edited != *settings {
+        *settings = edited;
+    }
+    if edited_bindings != *bindings {
+        *bindings = edited_bindings;
+    }
+}
+
+fn apply_music_volume(settings: Res<UserSettings>, sinks: Query<&AudioSink, With<Music>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for sink in &sinks {
+        sink.set_volume(settings.music_volume);
+    }
+}
+
+fn apply_aim_mode(settings: Res<UserSettings>, mut gameplay: ResMut<GameplaySettings>) {
+    if settings.is_changed() {
+        gameplay.aim_mode = settings.aim_mode;
+    }
+}
+
+/// Writes the settings once the window is closed, not on every frame a slider is dragged.
+fn save_settings(
+    settings: Res<UserSettings>,
+    window: Res<SettingsWindow>,
+    mut unsaved: Local<bool>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *unsaved = true;
+    }
+    if *unsaved && !window.open {
+        save::store(SETTINGS_SAVE_KEY, &*settings);
+        *unsaved = false;
+    }
+}
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::input::bindings::{Action, ActionInput};
 use crate::states::MainState;
 
 pub fn despawn_recursive_by_component<T: bevy::prelude::Component>(
@@ -10,11 +11,8 @@ pub fn despawn_recursive_by_component<T: bevy::prelude::Component>(
         commands.entity(e).despawn_recursive();
     }
 }
-pub fn exit_to_menu_on_escape(
-    input: Res<ButtonInput<KeyCode>>,
-    mut next_state: ResMut<NextState<MainState>>,
-) {
-    if input.just_released(KeyCode::Escape) {
+pub fn exit_to_menu_on_pause(actions: ActionInput, mut next_state: ResMut<NextState<MainState>>) {
+    if actions.just_released(Action::Pause) {
         next_state.set(MainState::Menu);
     }
 }
@@ -0,0 +1,55 @@
+use coins_bevy::headless::{simulate, Shot, Simulation};
+
+const LEVEL: &str = "assets/scenes/01.scn.ron";
+
+fn shot(angle: f32, strength: f32, spin: f32) -> Shot {
+    Shot {
+        angle,
+        strength,
+        spin,
+        at: None,
+    }
+}
+
+fn solution() -> Vec<Shot> {
+    vec![
+        shot(20.0, 200.0, 0.0),
+        shot(340.0, 200.0, 0.0),
+        shot(340.0, 117.08333, 0.0),
+    ]
+}
+
+#[test]
+fn known_shots_solve_the_level() {
+    let report = simulate(LEVEL, &solution()).unwrap();
+    assert!(report.solved);
+    assert_eq!(report.moves, 3);
+    assert_eq!(report.touches, 0);
+}
+
+#[test]
+fn missing_the_last_shot_does_not_solve_the_level() {
+    let report = simulate(LEVEL, &solution()[..2]).unwrap();
+    assert!(!report.solved);
+    assert_eq!(report.moves, 2);
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let shots = [shot(-30.0, 150.0, 0.5), shot(120.0, 90.0, 0.0)];
+    let first = simulate(LEVEL, &shots).unwrap();
+    let second = simulate(LEVEL, &shots).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn restoring_a_snapshot_plays_the_shot_again() {
+    let mut simulation = Simulation::from_file(LEVEL).unwrap();
+    let snapshot = simulation.snapshot();
+    simulation.shoot(solution()[0]);
+    let first = simulation.report();
+    simulation.restore(&snapshot);
+    assert_eq!(simulation.report().moves, 0);
+    simulation.shoot(solution()[0]);
+    assert_eq!(simulation.report(), first);
+}
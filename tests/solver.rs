@@ -0,0 +1,19 @@
+use coins_bevy::solver::{solve_file, SolverOptions};
+
+#[test]
+fn solver_finds_a_solution_for_the_first_level() {
+    // A coarser grid than the default, to keep the test short.
+    let options = SolverOptions {
+        angle_steps: 36,
+        strength_steps: 3,
+        spin_steps: 1,
+        max_moves: 3,
+        ..Default::default()
+    };
+    let solution = solve_file("assets/scenes/01.scn.ron", &options)
+        .unwrap()
+        .expect("the first level is solvable in three moves");
+    assert!(solution.report.solved);
+    assert!(solution.shots.len() <= 3);
+    assert_eq!(solution.report.moves as usize, solution.shots.len());
+}